@@ -1,18 +1,136 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, ToSql};
 use std::path::PathBuf;
+use tracing::{instrument, warn};
 
-use crate::models::{ReminderConfig, Task};
+use crate::models::{ChecklistItem, Priority, ReminderConfig, Task};
+
+/// Columns selected for every task query, in the order `row_to_task` expects
+const TASK_COLUMNS: &str = "id, title, description, priority, created_at, due_at, completed_at, \
+     tags, project, estimated_minutes, snoozed_until, checklist, label, all_day, reminders_enabled, blocked, attachments";
+
+fn parse_timestamp(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+}
+
+fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        priority: serde_json::from_str(&row.get::<_, String>(3)?).unwrap(),
+        created_at: parse_timestamp(&row.get::<_, String>(4)?),
+        due_at: row.get::<_, Option<String>>(5)?.map(|s| parse_timestamp(&s)),
+        completed_at: row.get::<_, Option<String>>(6)?.map(|s| parse_timestamp(&s)),
+        tags: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
+        project: row.get(8)?,
+        estimated_minutes: row.get(9)?,
+        snoozed_until: row.get::<_, Option<String>>(10)?.map(|s| parse_timestamp(&s)),
+        checklist: row
+            .get::<_, Option<String>>(11)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        label: row.get(12)?,
+        all_day: row.get(13)?,
+        reminders_enabled: row.get(14)?,
+        blocked: row.get(15)?,
+        attachments: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// Returned when a write would give two pending tasks the same title
+#[derive(Debug)]
+pub struct DuplicateTitle(pub String);
+
+impl std::fmt::Display for DuplicateTitle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Task '{}' already exists", self.0)
+    }
+}
+
+impl std::error::Error for DuplicateTitle {}
+
+/// Map a unique-constraint failure on a task write to `DuplicateTitle`
+fn map_duplicate_title(err: rusqlite::Error, title: &str) -> anyhow::Error {
+    match err.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => DuplicateTitle(title.to_string()).into(),
+        _ => err.into(),
+    }
+}
+
+/// Criteria for `Database::query_tasks`; unset fields match everything
+#[derive(Debug, Default)]
+pub struct TaskFilter {
+    pub include_completed: bool,
+    pub project: Option<String>,
+    pub estimate_min: Option<u32>,
+    pub estimate_max: Option<u32>,
+    /// Only tasks marked blocked
+    pub blocked_only: bool,
+    /// Only tasks that `Task::is_actionable`
+    pub actionable_only: bool,
+}
+
+impl TaskFilter {
+    /// The narrowing criteria in effect, as `key=value` strings for messages
+    pub fn describe(&self) -> Vec<String> {
+        let mut active = Vec::new();
+        if let Some(project) = &self.project {
+            active.push(format!("project={}", project));
+        }
+        if let Some(min) = self.estimate_min {
+            active.push(format!("estimate>={}m", min));
+        }
+        if let Some(max) = self.estimate_max {
+            active.push(format!("estimate<={}m", max));
+        }
+        if self.blocked_only {
+            active.push("blocked=true".to_string());
+        }
+        if self.actionable_only {
+            active.push("actionable=true".to_string());
+        }
+        active
+    }
+}
+
+/// Result of `Database::select`: column names and each row's values
+#[derive(Debug)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
 
 pub struct Database {
     conn: Connection,
 }
 
 impl Database {
+    #[instrument(level = "debug", err)]
     pub fn new(path: PathBuf) -> Result<Self> {
+        // A fresh install has no data directory yet
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
         let conn = Connection::open(path).context("Failed to open database")?;
+        Self::with_connection(conn)
+    }
 
+    /// Open an empty, throwaway database with the full schema, for tests
+    #[cfg(test)]
+    #[instrument(level = "debug", err)]
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory database")?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
         let db = Self { conn };
         db.init()?;
         Ok(db)
@@ -43,13 +161,85 @@ impl Database {
             [],
         )?;
 
+        // Titles are unique among pending tasks, ignoring ASCII case like the
+        // duplicate check in `add`; completed tasks may reuse a title. A
+        // database that already holds duplicates keeps working without it.
+        if let Err(err) = self.conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_tasks_pending_title
+             ON tasks (lower(title)) WHERE completed_at IS NULL",
+            [],
+        ) {
+            if err.sqlite_error_code() != Some(rusqlite::ErrorCode::ConstraintViolation) {
+                return Err(err.into());
+            }
+            // Without the index, concurrent adds can create duplicates again;
+            // `add` and `list` tell the user which titles to fix
+            let titles = self.duplicate_pending_titles()?;
+            warn!(?titles, "pending tasks share titles; unique title index not created");
+        }
+
+        // Columns added after the initial schema
+        self.add_column_if_missing("snoozed_until", "TEXT")?;
+        self.add_column_if_missing("sort_order", "INTEGER")?;
+        self.add_column_if_missing("checklist", "TEXT")?;
+        self.add_column_if_missing("label", "TEXT")?;
+        if self.add_column_if_missing("all_day", "INTEGER NOT NULL DEFAULT 0")? {
+            // Date-only due dates were stored as 23:59:59 before the flag existed
+            self.conn.execute(
+                "UPDATE tasks SET all_day = 1 WHERE due_at LIKE '%T23:59:59+00:00'",
+                [],
+            )?;
+        }
+
+        self.add_column_if_missing("reminders_enabled", "INTEGER NOT NULL DEFAULT 1")?;
+        self.add_column_if_missing("blocked", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("attachments", "TEXT")?;
+
+        // Manual ordering defaults to insertion order
+        self.conn.execute(
+            "UPDATE tasks SET sort_order = id WHERE sort_order IS NULL",
+            [],
+        )?;
+
         Ok(())
     }
 
+    /// Add a column to the tasks table unless an earlier run already did;
+    /// returns whether it was added
+    fn add_column_if_missing(&self, column: &str, definition: &str) -> Result<bool> {
+        let mut stmt = self.conn.prepare("SELECT name FROM pragma_table_info('tasks')")?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .any(|name| name == column);
+
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE tasks ADD COLUMN {} {}", column, definition),
+                [],
+            )?;
+        }
+        Ok(!exists)
+    }
+
+    /// Titles held by more than one pending task, ignoring case
+    pub fn duplicate_pending_titles(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT title FROM tasks WHERE completed_at IS NULL
+             GROUP BY lower(title) HAVING COUNT(*) > 1 ORDER BY lower(title)",
+        )?;
+        let titles = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(titles)
+    }
+
+    #[instrument(level = "debug", skip(self, task), fields(title = %task.title), err)]
     pub fn add_task(&self, task: &Task) -> Result<i64> {
         self.conn.execute(
-            "INSERT INTO tasks (title, description, priority, created_at, due_at, completed_at, tags, project, estimated_minutes)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            "INSERT INTO tasks (title, description, priority, created_at, due_at, completed_at, tags, project, estimated_minutes, snoozed_until, checklist, label, all_day, reminders_enabled, attachments)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
             params![
                 task.title,
                 task.description,
@@ -60,131 +250,163 @@ impl Database {
                 serde_json::to_string(&task.tags)?,
                 task.project,
                 task.estimated_minutes,
+                task.snoozed_until.map(|d| d.to_rfc3339()),
+                serde_json::to_string(&task.checklist)?,
+                task.label,
+                task.all_day,
+                task.reminders_enabled,
+                serde_json::to_string(&task.attachments)?,
             ],
+        )
+        .map_err(|e| map_duplicate_title(e, &task.title))?;
+
+        let id = self.conn.last_insert_rowid();
+        self.conn.execute(
+            "UPDATE tasks SET sort_order = id WHERE id = ?1",
+            params![id],
         )?;
 
-        Ok(self.conn.last_insert_rowid())
+        Ok(id)
     }
 
+    #[instrument(level = "debug", skip(self), err)]
     pub fn get_task(&self, id: i64) -> Result<Option<Task>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, priority, created_at, due_at, completed_at, tags, project, estimated_minutes
-             FROM tasks WHERE id = ?1"
-        )?;
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM tasks WHERE id = ?1", TASK_COLUMNS))?;
 
-        let task = stmt
-            .query_row(params![id], |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    priority: serde_json::from_str(&row.get::<_, String>(3)?).unwrap(),
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    due_at: row.get::<_, Option<String>>(5)?.map(|s| {
-                        DateTime::parse_from_rfc3339(&s)
-                            .unwrap()
-                            .with_timezone(&Utc)
-                    }),
-                    completed_at: row.get::<_, Option<String>>(6)?.map(|s| {
-                        DateTime::parse_from_rfc3339(&s)
-                            .unwrap()
-                            .with_timezone(&Utc)
-                    }),
-                    tags: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
-                    project: row.get(8)?,
-                    estimated_minutes: row.get(9)?,
-                })
-            })
-            .optional()?;
+        let task = stmt.query_row(params![id], row_to_task).optional()?;
 
         Ok(task)
     }
 
+    #[instrument(level = "debug", skip(self), err)]
     pub fn list_tasks(&self, include_completed: bool) -> Result<Vec<Task>> {
-        let query = if include_completed {
-            "SELECT id, title, description, priority, created_at, due_at, completed_at, tags, project, estimated_minutes
-             FROM tasks ORDER BY priority DESC, created_at ASC"
+        self.query_tasks(&TaskFilter {
+            include_completed,
+            ..TaskFilter::default()
+        })
+    }
+
+    /// List tasks matching every criterion in `filter`, in queue order
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn query_tasks(&self, filter: &TaskFilter) -> Result<Vec<Task>> {
+        let mut clauses: Vec<&str> = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if !filter.include_completed {
+            clauses.push("completed_at IS NULL");
+        }
+        if let Some(ref project) = filter.project {
+            clauses.push("project = ?");
+            values.push(Box::new(project.clone()));
+        }
+        // Comparisons against NULL are never true, so tasks without an
+        // estimate drop out as soon as either bound is given
+        if let Some(min) = filter.estimate_min {
+            clauses.push("estimated_minutes >= ?");
+            values.push(Box::new(min));
+        }
+        if let Some(max) = filter.estimate_max {
+            clauses.push("estimated_minutes <= ?");
+            values.push(Box::new(max));
+        }
+        if filter.blocked_only {
+            clauses.push("blocked = 1");
+        }
+
+        let where_clause = if clauses.is_empty() {
+            String::new()
         } else {
-            "SELECT id, title, description, priority, created_at, due_at, completed_at, tags, project, estimated_minutes
-             FROM tasks WHERE completed_at IS NULL ORDER BY priority DESC, created_at ASC"
+            format!("WHERE {}", clauses.join(" AND "))
         };
+        let query = format!(
+            "SELECT {} FROM tasks {} ORDER BY sort_order ASC",
+            TASK_COLUMNS, where_clause
+        );
 
-        let mut stmt = self.conn.prepare(query)?;
-        let tasks = stmt.query_map([], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                priority: serde_json::from_str(&row.get::<_, String>(3)?).unwrap(),
-                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                due_at: row.get::<_, Option<String>>(5)?.map(|s| {
-                    DateTime::parse_from_rfc3339(&s)
-                        .unwrap()
-                        .with_timezone(&Utc)
-                }),
-                completed_at: row.get::<_, Option<String>>(6)?.map(|s| {
-                    DateTime::parse_from_rfc3339(&s)
-                        .unwrap()
-                        .with_timezone(&Utc)
-                }),
-                tags: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
-                project: row.get(8)?,
-                estimated_minutes: row.get(9)?,
-            })
-        })?;
+        let mut stmt = self.conn.prepare(&query)?;
+        let mut tasks = stmt
+            .query_map(params_from_iter(values.iter()), row_to_task)?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        tasks.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+        // Priority is stored as its JSON name, which doesn't sort by urgency
+        // in SQL; a stable sort keeps manual order within each priority
+        tasks.sort_by(|a, b| b.priority.cmp(&a.priority));
+        if filter.actionable_only {
+            tasks.retain(Task::is_actionable);
+        }
+        Ok(tasks)
     }
 
+    #[instrument(level = "debug", skip(self), err)]
     pub fn get_next_task(&self) -> Result<Option<Task>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE completed_at IS NULL
+             ORDER BY due_at ASC, sort_order ASC",
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt
+            .query_map([], row_to_task)?
+            .filter(|task| task.as_ref().map_or(true, Task::is_actionable))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // First of the highest priority, keeping the due date order within it
+        let top = tasks.iter().map(|t| &t.priority).max().cloned();
+        Ok(tasks.into_iter().find(|t| Some(&t.priority) == top.as_ref()))
+    }
+
+    /// Every project name in use, pending or completed, sorted
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn distinct_projects(&self) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, description, priority, created_at, due_at, completed_at, tags, project, estimated_minutes
-             FROM tasks WHERE completed_at IS NULL
-             ORDER BY priority DESC, due_at ASC, created_at ASC LIMIT 1"
+            "SELECT DISTINCT project FROM tasks WHERE project IS NOT NULL ORDER BY project",
         )?;
+        let projects = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(projects)
+    }
 
-        let task = stmt
-            .query_row([], |row| {
-                Ok(Task {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    description: row.get(2)?,
-                    priority: serde_json::from_str(&row.get::<_, String>(3)?).unwrap(),
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    due_at: row.get::<_, Option<String>>(5)?.map(|s| {
-                        DateTime::parse_from_rfc3339(&s)
-                            .unwrap()
-                            .with_timezone(&Utc)
-                    }),
-                    completed_at: row.get::<_, Option<String>>(6)?.map(|s| {
-                        DateTime::parse_from_rfc3339(&s)
-                            .unwrap()
-                            .with_timezone(&Utc)
-                    }),
-                    tags: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
-                    project: row.get(8)?,
-                    estimated_minutes: row.get(9)?,
-                })
-            })
-            .optional()?;
-
-        Ok(task)
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn count_pending(&self) -> Result<u64> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE completed_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
     }
 
-    pub fn complete_task(&self, id: i64) -> Result<bool> {
+    /// Complete a pending task, now or at a given (earlier) time
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn complete_task(&self, id: i64, at: Option<DateTime<Utc>>) -> Result<bool> {
         let rows = self.conn.execute(
             "UPDATE tasks SET completed_at = ?1 WHERE id = ?2 AND completed_at IS NULL",
-            params![Utc::now().to_rfc3339(), id],
+            params![at.unwrap_or_else(Utc::now).to_rfc3339(), id],
         )?;
         Ok(rows > 0)
     }
 
+    /// Complete every given task in one transaction, returning how many were
+    /// still pending
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn complete_tasks(&self, ids: &[i64], at: Option<DateTime<Utc>>) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let now = at.unwrap_or_else(Utc::now).to_rfc3339();
+        let mut completed = 0;
+        for id in ids {
+            completed += tx.execute(
+                "UPDATE tasks SET completed_at = ?1 WHERE id = ?2 AND completed_at IS NULL",
+                params![now, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(completed)
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
     pub fn delete_task(&self, id: i64) -> Result<bool> {
         let rows = self
             .conn
@@ -192,6 +414,7 @@ impl Database {
         Ok(rows > 0)
     }
 
+    #[instrument(level = "debug", skip(self), err)]
     pub fn clear_completed(&self) -> Result<u64> {
         let rows = self
             .conn
@@ -199,16 +422,25 @@ impl Database {
         Ok(rows as u64)
     }
 
-    pub fn reset_all(&self) -> Result<u64> {
-        let rows = self.conn.execute("DELETE FROM tasks", [])?;
+    /// Delete every task. With `restart_ids`, also forget the AUTOINCREMENT
+    /// high-water mark so the next task gets id 1 again.
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn reset_all(&self, restart_ids: bool) -> Result<u64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let rows = tx.execute("DELETE FROM tasks", [])?;
+        if restart_ids {
+            tx.execute("DELETE FROM sqlite_sequence WHERE name = 'tasks'", [])?;
+        }
+        tx.commit()?;
         Ok(rows as u64)
     }
 
+    #[instrument(level = "debug", skip(self, task), err)]
     pub fn update_task(&self, id: i64, task: &Task) -> Result<bool> {
         let rows = self.conn.execute(
             "UPDATE tasks SET title = ?1, description = ?2, priority = ?3, 
-             due_at = ?4, tags = ?5, project = ?6, estimated_minutes = ?7 
-             WHERE id = ?8",
+             due_at = ?4, tags = ?5, project = ?6, estimated_minutes = ?7, label = ?8, 
+             all_day = ?9, reminders_enabled = ?10 WHERE id = ?11",
             params![
                 task.title,
                 task.description,
@@ -217,61 +449,122 @@ impl Database {
                 serde_json::to_string(&task.tags)?,
                 task.project,
                 task.estimated_minutes,
+                task.label,
+                task.all_day,
+                task.reminders_enabled,
                 id,
             ],
+        )
+        .map_err(|e| map_duplicate_title(e, &task.title))?;
+        Ok(rows > 0)
+    }
+
+    /// Set the priority of every given task in one transaction, returning
+    /// how many actually changed
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn set_priorities(&self, ids: &[i64], priority: &Priority) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let value = serde_json::to_string(priority)?;
+        let mut changed = 0;
+        for id in ids {
+            changed += tx.execute(
+                "UPDATE tasks SET priority = ?1 WHERE id = ?2 AND priority != ?1",
+                params![value, id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    /// Exchange the manual ordering positions of two tasks
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn swap_tasks(&self, first: i64, second: i64) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let order_of = |id: i64| -> Result<i64> {
+            Ok(tx.query_row(
+                "SELECT sort_order FROM tasks WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )?)
+        };
+        let first_order = order_of(first)?;
+        let second_order = order_of(second)?;
+
+        tx.execute(
+            "UPDATE tasks SET sort_order = ?1 WHERE id = ?2",
+            params![second_order, first],
+        )?;
+        tx.execute(
+            "UPDATE tasks SET sort_order = ?1 WHERE id = ?2",
+            params![first_order, second],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self, checklist), err)]
+    pub fn set_checklist(&self, id: i64, checklist: &[ChecklistItem]) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE tasks SET checklist = ?1 WHERE id = ?2",
+            params![serde_json::to_string(checklist)?, id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    #[instrument(level = "debug", skip(self, attachments), err)]
+    pub fn set_attachments(&self, id: i64, attachments: &[String]) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE tasks SET attachments = ?1 WHERE id = ?2",
+            params![serde_json::to_string(attachments)?, id],
         )?;
         Ok(rows > 0)
     }
 
+    /// Suppress reminders for a task until the given time (`None` clears it)
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn snooze_task(&self, id: i64, until: Option<DateTime<Utc>>) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE tasks SET snoozed_until = ?1 WHERE id = ?2",
+            params![until.map(|d| d.to_rfc3339()), id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn set_blocked(&self, id: i64, blocked: bool) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE tasks SET blocked = ?1 WHERE id = ?2 AND blocked != ?1",
+            params![blocked, id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    #[instrument(level = "debug", skip(self), err)]
     pub fn get_config(&self) -> Result<ReminderConfig> {
-        let mut stmt = self
+        let value: Option<String> = self
             .conn
-            .prepare("SELECT value FROM config WHERE key = 'reminder_config'")?;
-        let config: Option<ReminderConfig> = stmt
-            .query_row([], |row| {
-                let value: String = row.get(0)?;
-                serde_json::from_str(&value)
-                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
-            })
+            .query_row(
+                "SELECT value FROM config WHERE key = 'reminder_config'",
+                [],
+                |row| row.get(0),
+            )
             .optional()?;
 
-        // Fill missing fields with default values
-        if config.is_some() {
-            // Check and add missing fields
-            let json_value = self
-                .conn
-                .query_row(
-                    "SELECT value FROM config WHERE key = 'reminder_config'",
-                    [],
-                    |row| row.get::<_, String>(0),
-                )?;
-            
-            let mut parsed: serde_json::Value = serde_json::from_str(&json_value)?;
-            
-            // Add missing fields
-            if parsed.get("wall_quiet_start_hour").is_none() {
-                parsed["wall_quiet_start_hour"] = serde_json::Value::Number(18.into());
-            }
-            if parsed.get("wall_quiet_end_hour").is_none() {
-                parsed["wall_quiet_end_hour"] = serde_json::Value::Number(9.into());
+        match value {
+            Some(value) => {
+                // Missing fields are filled with defaults; save them back so
+                // external readers (install.sh) see the complete config
+                let config: ReminderConfig = serde_json::from_str(&value)?;
+                if serde_json::to_string(&config)? != value {
+                    self.save_config(&config)?;
+                }
+                Ok(config)
             }
-            if parsed.get("start_from_quiet_end").is_none() {
-                parsed["start_from_quiet_end"] = serde_json::Value::Bool(false);
-            }
-            
-            // Save updated config
-            let updated_value = serde_json::to_string(&parsed)?;
-            self.conn.execute(
-                "UPDATE config SET value = ?1 WHERE key = 'reminder_config'",
-                params![updated_value],
-            )?;
-            
-            Ok(serde_json::from_value(parsed)?)
-        } else {
-            Ok(ReminderConfig::default())
+            None => Ok(ReminderConfig::default()),
         }
     }
 
+    #[instrument(level = "debug", skip_all, err)]
     pub fn save_config(&self, config: &ReminderConfig) -> Result<()> {
         let value = serde_json::to_string(config)?;
         self.conn.execute(
@@ -280,4 +573,198 @@ impl Database {
         )?;
         Ok(())
     }
+
+    /// Run a single user-supplied SELECT statement. Anything that isn't a
+    /// read-only SELECT is rejected before it runs.
+    #[instrument(level = "debug", skip(self), err)]
+    pub fn select(&self, sql: &str) -> Result<QueryResult> {
+        let is_select = sql
+            .split_whitespace()
+            .next()
+            .is_some_and(|word| word.eq_ignore_ascii_case("select"));
+        if !is_select {
+            return Err(anyhow::anyhow!("Only SELECT statements are allowed"));
+        }
+        // `prepare` compiles only the first statement and ignores the rest
+        let sql = sql.trim().trim_end_matches(';');
+        if sql.contains(';') {
+            return Err(anyhow::anyhow!("Only a single statement is allowed"));
+        }
+
+        let mut stmt = self.conn.prepare(sql)?;
+        if !stmt.readonly() {
+            return Err(anyhow::anyhow!("Only read-only statements are allowed"));
+        }
+
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let column_count = columns.len();
+        let rows = stmt
+            .query_map([], |row| {
+                (0..column_count)
+                    .map(|idx| {
+                        Ok(match row.get_ref(idx)? {
+                            ValueRef::Null => serde_json::Value::Null,
+                            ValueRef::Integer(i) => i.into(),
+                            ValueRef::Real(f) => f.into(),
+                            ValueRef::Text(t) => String::from_utf8_lossy(t).into(),
+                            ValueRef::Blob(b) => format!("<{} bytes>", b.len()).into(),
+                        })
+                    })
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(QueryResult { columns, rows })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_creates_missing_parent_directories() {
+        let root = std::env::temp_dir().join(format!("todo-queue-test-{}-{}", std::process::id(), line!()));
+        let path = root.join("nested").join("deeper").join("tasks.db");
+        assert!(!path.parent().unwrap().exists());
+
+        let db = Database::new(path.clone()).unwrap();
+        db.add_task(&Task::sample("First")).unwrap();
+        assert!(path.exists());
+
+        drop(db);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn finds_duplicate_pending_titles() {
+        let db = Database::in_memory().unwrap();
+        db.conn.execute("DROP INDEX idx_tasks_pending_title", []).unwrap();
+        for title in ["Milk", "milk", "Bread", "Eggs"] {
+            db.add_task(&Task::sample(title)).unwrap();
+        }
+        let eggs = db.add_task(&Task::sample("Eggs")).unwrap();
+        assert_eq!(db.duplicate_pending_titles().unwrap().len(), 2);
+
+        db.complete_task(eggs, None).unwrap();
+        let titles = db.duplicate_pending_titles().unwrap();
+        assert_eq!(titles.len(), 1);
+        assert!(titles[0].eq_ignore_ascii_case("milk"));
+    }
+
+    #[test]
+    fn added_task_round_trips() {
+        let db = Database::in_memory().unwrap();
+        let mut task = Task::sample("Write report");
+        task.description = Some("Quarterly numbers".to_string());
+        task.priority = Priority::High;
+        task.tags = vec!["work".to_string()];
+        task.project = Some("office".to_string());
+        task.estimated_minutes = Some(45);
+        let id = db.add_task(&task).unwrap();
+
+        let stored = db.get_task(id).unwrap().unwrap();
+        assert_eq!(stored.title, "Write report");
+        assert_eq!(stored.description.as_deref(), Some("Quarterly numbers"));
+        assert_eq!(stored.priority, Priority::High);
+        assert_eq!(stored.tags, vec!["work".to_string()]);
+        assert_eq!(stored.project.as_deref(), Some("office"));
+        assert_eq!(stored.estimated_minutes, Some(45));
+        assert!(!stored.is_completed());
+    }
+
+    #[test]
+    fn list_hides_completed_tasks_unless_asked() {
+        let db = Database::in_memory().unwrap();
+        let done = db.add_task(&Task::sample("Done")).unwrap();
+        db.add_task(&Task::sample("Open")).unwrap();
+        assert!(db.complete_task(done, None).unwrap());
+
+        let pending: Vec<String> = db.list_tasks(false).unwrap().into_iter().map(|t| t.title).collect();
+        assert_eq!(pending, vec!["Open".to_string()]);
+        assert_eq!(db.list_tasks(true).unwrap().len(), 2);
+        assert_eq!(db.count_pending().unwrap(), 1);
+    }
+
+    #[test]
+    fn complete_task_only_completes_once() {
+        let db = Database::in_memory().unwrap();
+        let id = db.add_task(&Task::sample("Once")).unwrap();
+        let at = Utc::now() - chrono::Duration::hours(2);
+
+        assert!(db.complete_task(id, Some(at)).unwrap());
+        assert!(!db.complete_task(id, None).unwrap());
+        let stored = db.get_task(id).unwrap().unwrap();
+        assert_eq!(stored.completed_at.map(|t| t.timestamp()), Some(at.timestamp()));
+    }
+
+    #[test]
+    fn query_tasks_sorts_critical_first() {
+        let db = Database::in_memory().unwrap();
+        for (title, priority) in [("low", Priority::Low), ("critical", Priority::Critical), ("medium", Priority::Medium)] {
+            let mut task = Task::sample(title);
+            task.priority = priority;
+            db.add_task(&task).unwrap();
+        }
+
+        let titles: Vec<String> = db
+            .query_tasks(&TaskFilter::default())
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["critical", "medium", "low"]);
+    }
+
+    /// A database with one task per status, most urgent first, so the order
+    /// alone would put the non-actionable ones ahead
+    fn mixed_status_db() -> Database {
+        let db = Database::in_memory().unwrap();
+        let add = |title: &str, priority: Priority, edit: &dyn Fn(&mut Task)| {
+            let mut task = Task::sample(title);
+            task.priority = priority;
+            edit(&mut task);
+            db.add_task(&task).unwrap()
+        };
+        let completed = add("completed", Priority::Critical, &|_| {});
+        db.complete_task(completed, None).unwrap();
+        let blocked = add("blocked", Priority::Critical, &|_| {});
+        db.set_blocked(blocked, true).unwrap();
+        add("snoozed", Priority::High, &|t| t.snoozed_until = Some(Utc::now() + chrono::Duration::hours(1)));
+        add("no-remind", Priority::Medium, &|t| t.reminders_enabled = false);
+        db
+    }
+
+    #[test]
+    fn next_task_skips_completed_and_blocked_but_not_snoozed() {
+        let db = mixed_status_db();
+        assert_eq!(db.get_next_task().unwrap().unwrap().title, "snoozed");
+    }
+
+    #[test]
+    fn actionable_filter_keeps_snoozed_and_no_remind() {
+        let db = mixed_status_db();
+        let titles: Vec<String> = db
+            .query_tasks(&TaskFilter {
+                include_completed: true,
+                actionable_only: true,
+                ..TaskFilter::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, ["snoozed", "no-remind"]);
+    }
+
+    #[test]
+    fn complete_tasks_counts_only_pending() {
+        let db = Database::in_memory().unwrap();
+        let a = db.add_task(&Task::sample("A")).unwrap();
+        let b = db.add_task(&Task::sample("B")).unwrap();
+        db.complete_task(a, None).unwrap();
+
+        assert_eq!(db.complete_tasks(&[a, b], None).unwrap(), 1);
+        assert_eq!(db.count_pending().unwrap(), 0);
+    }
 }
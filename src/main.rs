@@ -1,25 +1,76 @@
 mod database;
 mod models;
 mod reminders;
+mod top;
 mod ui;
 
-use anyhow::Result;
-use chrono::{DateTime, Duration, Utc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::path::PathBuf;
 
-use database::Database;
-use models::{Priority, Task};
+use database::{Database, DuplicateTitle, TaskFilter};
+use models::{ChecklistItem, Priority, ReminderConfig, Task, WeekStart};
 
 #[derive(Parser)]
 #[command(name = "todo")]
 #[command(about = "Intelligent CLI Task Management System", long_about = None)]
 struct Cli {
+    /// Report errors as JSON on stderr (for scripting)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Replace emoji with short text tokens, keeping colors and layout
+    #[arg(long, global = true)]
+    no_emoji: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Subcommand)]
+enum CheckAction {
+    /// Add a step to the checklist
+    Add {
+        /// Step text
+        text: String,
+    },
+    /// Mark a step done, or undone if it already is
+    Toggle {
+        /// Step number as shown by 'todo show'
+        step: usize,
+    },
+    /// Remove a step from the checklist
+    Remove {
+        /// Step number as shown by 'todo show'
+        step: usize,
+    },
+}
+
+/// Layouts for `todo list`
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum ListFormat {
+    /// Multi-line cards with every detail
+    Default,
+    /// One table row per task with every column
+    Wide,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Set any setting by name, e.g. `todo config set daily_goal 5`
+    Set {
+        /// Setting name as shown by `todo config --json`
+        key: String,
+        /// New value (`none` clears optional settings)
+        value: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Add a new task
@@ -29,14 +80,14 @@ enum Commands {
         /// Task description
         #[arg(short, long)]
         description: Option<String>,
-        /// Priority (low, medium, high, critical)
+        /// Priority (low, medium, high, critical, or a configured label)
         #[arg(short, long, default_value = "medium")]
         priority: String,
-        /// Due time (e.g., "2024-12-31 23:59" or "2h", "1d", "1w")
-        #[arg(short, long)]
+        /// Due time (e.g., "2024-12-31 23:59", "9:30pm" or "2h", "1d", "1w")
+        #[arg(long)]
         due: Option<String>,
-        /// Project tag
-        #[arg(short, long)]
+        /// Project tag (defaults to $TODO_QUEUE_PROJECT or ./.todo-queue-project)
+        #[arg(long)]
         project: Option<String>,
         /// Task tags (comma separated)
         #[arg(short, long)]
@@ -44,6 +95,15 @@ enum Commands {
         /// Estimated time in minutes
         #[arg(short, long)]
         estimate: Option<u32>,
+        /// Marker shown before the title (e.g., "🔥" or "WAIT")
+        #[arg(long)]
+        label: Option<String>,
+        /// Never mention this task in reminders
+        #[arg(long)]
+        no_remind: bool,
+        /// Attach a file path or URL (repeatable)
+        #[arg(long = "attachment", value_name = "PATH_OR_URL")]
+        attachments: Vec<String>,
     },
     /// List all tasks
     List {
@@ -56,18 +116,59 @@ enum Commands {
         /// Filter by project
         #[arg(short, long)]
         project: Option<String>,
+        /// Only tasks estimated at this many minutes or more
+        #[arg(long, value_name = "MINUTES")]
+        estimate_min: Option<u32>,
+        /// Only tasks estimated at this many minutes or fewer
+        #[arg(long, value_name = "MINUTES")]
+        estimate_max: Option<u32>,
+        /// Show only blocked tasks
+        #[arg(long)]
+        blocked: bool,
+        /// Show only tasks that can be worked on now (pending and not blocked)
+        #[arg(long, conflicts_with_all = ["blocked", "completed", "all"])]
+        actionable: bool,
+        /// Output layout
+        #[arg(long, value_enum, default_value_t = ListFormat::Default)]
+        format: ListFormat,
+        /// Also print each task's stable database id, which survives list changes
+        /// and can be passed as id:<n> wherever a task index is accepted
+        #[arg(long)]
+        show_id: bool,
+        /// Stable tab-separated output for scripts: id, status, priority weight, due epoch, title
+        #[arg(long, conflicts_with_all = ["format", "show_id"])]
+        porcelain: bool,
     },
     /// Show next task
-    Next,
+    Next {
+        /// Print only the number of pending tasks (for shell prompts)
+        #[arg(long)]
+        count_only: bool,
+    },
+    /// Randomly pick a pending task, weighted by priority
+    Pick {
+        /// Also favor overdue and soon-due tasks
+        #[arg(short, long)]
+        urgency: bool,
+        /// Seed for a reproducible pick
+        #[arg(long)]
+        seed: Option<u64>,
+    },
     /// Complete a task
     Done {
-        /// Task index or title
+        /// Task index, title, or id:<n>
         #[arg(value_name = "INDEX_OR_TITLE")]
         target: Option<String>,
+        /// Complete every pending task in this project instead
+        #[arg(long, conflicts_with = "target")]
+        project: Option<String>,
+        /// When the task was actually finished (e.g., '2h', '2024-01-01 17:00')
+        #[arg(long, value_name = "TIME")]
+        at: Option<String>,
     },
     /// Delete a task
     Delete {
-        /// Task index or title
+        /// Task index, title, or id:<n>
         #[arg(value_name = "INDEX_OR_TITLE")]
         target: String,
     },
@@ -75,15 +176,36 @@ enum Commands {
     Clear,
     /// Show task details
     Show {
-        /// Task index or title
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+    },
+    /// Manage a task's checklist of steps
+    Check {
+        /// Task index, title, or id:<n>
         #[arg(value_name = "INDEX_OR_TITLE")]
         target: String,
+        #[command(subcommand)]
+        action: CheckAction,
+    },
+    /// Attach a file path or URL to a task
+    Attach {
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+        /// File path or URL
+        #[arg(value_name = "PATH_OR_URL")]
+        reference: String,
+    },
+    /// Reset - delete all tasks (new task ids start again at 1)
+    Reset {
+        /// Keep counting ids from where they left off instead of restarting at 1
+        #[arg(long)]
+        keep_ids: bool,
     },
-    /// Reset - delete all tasks
-    Reset,
     /// Update a task
     Update {
-        /// Task index or title
+        /// Task index, title, or id:<n>
         #[arg(value_name = "INDEX_OR_TITLE")]
         target: String,
         /// New title
@@ -92,81 +214,514 @@ enum Commands {
         /// New description
         #[arg(short, long)]
         description: Option<String>,
-        /// New priority
+        /// New priority (low, medium, high, critical, or a configured label)
         #[arg(short, long)]
         priority: Option<String>,
-        /// New due time
-        #[arg(short, long)]
+        /// New due time (use "none" to clear the due date)
+        #[arg(long)]
         due: Option<String>,
         /// New project
-        #[arg(short, long)]
+        #[arg(long)]
         project: Option<String>,
         /// New tags (comma separated)
-        #[arg(short, long)]
+        #[arg(long)]
         tags: Option<String>,
         /// New estimated time in minutes
         #[arg(short, long)]
         estimate: Option<u32>,
+        /// New label shown before the title ("none" to remove it)
+        #[arg(long)]
+        label: Option<String>,
+        /// Stop mentioning this task in reminders
+        #[arg(long, conflicts_with = "remind")]
+        no_remind: bool,
+        /// Mention this task in reminders again
+        #[arg(long)]
+        remind: bool,
+    },
+    /// Rename a task (shortcut for `update --title`)
+    Rename {
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+        /// New title
+        new_title: String,
+    },
+    /// Push a task's due date back (adds to the current due date, or now)
+    Defer {
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+        /// How far to push it (e.g., "30m", "2h", "1d", "1w")
+        duration: String,
+    },
+    /// Raise or lower one task's priority, or set it for every task in a project
+    Bump {
+        /// Task index, title, or id:<n> to nudge
+        #[arg(value_name = "INDEX_OR_TITLE", requires = "direction", conflicts_with_all = ["project", "to"])]
+        target: Option<String>,
+        /// Direction to nudge the task (up, down)
+        direction: Option<String>,
+        /// Set the priority of all pending tasks in this project
+        #[arg(short, long, requires = "to")]
+        project: Option<String>,
+        /// Priority to set for the matched tasks
+        #[arg(long, requires = "project")]
+        to: Option<String>,
+    },
+    /// Exchange the queue positions of two pending tasks
+    Swap {
+        /// First task index or title
+        first: String,
+        /// Second task index or title
+        second: String,
+    },
+    /// Mark a task as blocked (waiting on something), keeping it out of next, pick and reminders
+    Block {
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+    },
+    /// Clear a task's blocked mark
+    Unblock {
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+    },
+    /// Pause reminders for a task without changing its due date
+    Snooze {
+        /// Task index, title, or id:<n>
+        #[arg(value_name = "INDEX_OR_TITLE")]
+        target: String,
+        /// How long to snooze (e.g., "30m", "2h", "1d"), or "none" to wake it
+        duration: String,
+    },
+    /// Project when each actionable task will be finished, working in urgency order within the daily capacity
+    Forecast,
+    /// Live dashboard of pending, overdue and next tasks (q to quit)
+    Top {
+        /// Seconds between refreshes
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
     },
     /// Check reminders
-    Remind,
+    Remind {
+        /// Send a test message through all enabled backends and report results
+        #[arg(long)]
+        test: bool,
+    },
+    /// Run a read-only SQL SELECT against the database (the schema is not a stable API)
+    Query {
+        /// A single SELECT statement, e.g. "SELECT project, COUNT(*) FROM tasks GROUP BY project"
+        sql: String,
+        /// Confirm that you want to run raw SQL
+        #[arg(long)]
+        allow_sql: bool,
+    },
     /// Show statistics
-    Stats,
+    Stats {
+        /// Only count tasks created or completed within this window (e.g., "7d", "4w")
+        #[arg(long)]
+        since: Option<String>,
+        /// Only count tasks in this project
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+    /// Edit the whole configuration as TOML in $VISUAL or $EDITOR
+    EditConfig,
     /// Configure reminder settings
+    #[command(group(clap::ArgGroup::new("settings").multiple(true)), args_conflicts_with_subcommands = true)]
     Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
         /// Enable or disable reminders
-        #[arg(short, long)]
+        #[arg(short, long, group = "settings")]
         enabled: Option<bool>,
         /// Reminder interval (e.g., "2h", "30m", or just "60" for minutes)
-        #[arg(short, long)]
+        #[arg(short, long, group = "settings")]
         interval: Option<String>,
         /// Enable desktop notifications
-        #[arg(long)]
+        #[arg(long, group = "settings")]
         notify: Option<bool>,
         /// Enable terminal wall messages
-        #[arg(long)]
+        #[arg(long, group = "settings")]
         wall: Option<bool>,
         /// Wall quiet hours start (0-23)
-        #[arg(long)]
+        #[arg(long, group = "settings")]
         wall_quiet_start: Option<u32>,
         /// Wall quiet hours end (0-23)
-        #[arg(long)]
+        #[arg(long, group = "settings")]
         wall_quiet_end: Option<u32>,
         /// Start reminders from quiet-end time
-        #[arg(long)]
+        #[arg(long, group = "settings")]
         start_from_quiet_end: Option<bool>,
+        /// First day of the week (monday, sunday)
+        #[arg(long, group = "settings")]
+        week_start: Option<String>,
+        /// Priority labels, low to critical (e.g., "P3,P2,P1,P0"), or "default"
+        #[arg(long, group = "settings")]
+        priority_labels: Option<String>,
+        /// Flag tasks due within this many hours as "due soon" in reminders
+        #[arg(long, group = "settings")]
+        due_soon_hours: Option<u32>,
+        /// Skip reminders after this many idle minutes (0 turns it off)
+        #[arg(long, value_name = "MINUTES", group = "settings")]
+        skip_if_idle: Option<u32>,
+        /// Minutes of task work per day assumed by forecast
+        #[arg(long, value_name = "MINUTES", group = "settings", value_parser = clap::value_parser!(u32).range(1..))]
+        daily_capacity: Option<u32>,
+        /// Estimate in minutes that forecast assumes for tasks without one
+        #[arg(long, value_name = "MINUTES", group = "settings")]
+        default_estimate: Option<u32>,
+        /// Tasks to complete per day, shown as progress in stats (0 turns it off)
+        #[arg(long, group = "settings")]
+        daily_goal: Option<u32>,
+        /// Character shown before tags (e.g., "#", "@", "+")
+        #[arg(long, group = "settings")]
+        tag_prefix: Option<String>,
+        /// Show a "no estimate" hint on tasks without an estimate
+        #[arg(long, group = "settings")]
+        estimate_hint: Option<bool>,
+        /// Play on `todo done`: "bell", a shell command, or "none"
+        #[arg(long, group = "settings")]
+        completion_sound: Option<String>,
+        /// Include tasks without a due date in reminders
+        #[arg(long, group = "settings")]
+        remind_undated: Option<bool>,
+        /// Also write reminders to a file for external pollers
+        #[arg(long, group = "settings")]
+        file_sink: Option<bool>,
+        /// File the reminder file sink writes to ("default" for ~/.todo-queue/reminder.txt)
+        #[arg(long, group = "settings")]
+        file_sink_path: Option<String>,
+        /// Append to the file sink instead of replacing it
+        #[arg(long, group = "settings")]
+        file_sink_append: Option<bool>,
+        /// Minutes past the due time before a task counts as overdue
+        #[arg(long, group = "settings")]
+        overdue_grace: Option<u32>,
+        /// Store project names in lowercase
+        #[arg(long, group = "settings")]
+        lowercase_projects: Option<bool>,
+        /// Reject new project names that differ from an existing one only by case
+        #[arg(long, group = "settings")]
+        strict_projects: Option<bool>,
+        /// Store tags in lowercase
+        #[arg(long, group = "settings")]
+        lowercase_tags: Option<bool>,
         /// Show current configuration
         #[arg(short, long)]
         show: bool,
+        /// Restore every setting to its default
+        #[arg(long, conflicts_with_all = ["settings", "show"])]
+        reset: bool,
     },
 }
 
-fn parse_priority(s: &str) -> Priority {
+/// Built-in priority names, ordered low to critical like custom labels
+const PRIORITY_NAMES: [&str; 4] = ["low", "medium", "high", "critical"];
+
+fn parse_priority(s: &str, config: &ReminderConfig) -> Result<Priority> {
+    let priorities = [Priority::Low, Priority::Medium, Priority::High, Priority::Critical];
+    let name = s.trim();
+    if let Some(labels) = &config.priority_labels {
+        if let Some(idx) = labels.iter().position(|l| l.eq_ignore_ascii_case(name)) {
+            return Ok(priorities[idx].clone());
+        }
+    }
+    if let Some(idx) = PRIORITY_NAMES.iter().position(|n| n.eq_ignore_ascii_case(name)) {
+        return Ok(priorities[idx].clone());
+    }
+
+    let mut valid: Vec<String> = PRIORITY_NAMES.iter().map(|n| n.to_string()).collect();
+    if let Some(labels) = &config.priority_labels {
+        valid.extend(labels.iter().cloned());
+    }
+    Err(anyhow::anyhow!("Unknown priority '{}'. Use one of: {}", name, valid.join(", ")))
+}
+
+/// Parse "P3,P2,P1,P0"-style labels (Low to Critical); "default" clears them
+fn parse_priority_labels(s: &str) -> Result<Option<[String; 4]>> {
+    if s.trim().eq_ignore_ascii_case("default") {
+        return Ok(None);
+    }
+
+    let labels: Vec<String> = s.split(',').map(|l| l.trim().to_string()).collect();
+    if labels.iter().any(|l| l.is_empty()) {
+        return Err(anyhow::anyhow!("Priority labels cannot be empty"));
+    }
+    let labels: [String; 4] = labels
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Expected 4 comma separated labels, ordered low to critical"))?;
+    validate_priority_labels(&labels)?;
+    Ok(Some(labels))
+}
+
+/// Labels are checked before built-in names in `parse_priority`, so each must
+/// be unique and must not shadow another level's name
+fn validate_priority_labels(labels: &[String; 4]) -> Result<()> {
+    for (idx, label) in labels.iter().enumerate() {
+        if labels[..idx].iter().any(|l| l.eq_ignore_ascii_case(label)) {
+            return Err(anyhow::anyhow!("Priority label '{}' is used more than once", label));
+        }
+        if let Some(level) = PRIORITY_NAMES.iter().position(|n| n.eq_ignore_ascii_case(label)) {
+            if level != idx {
+                return Err(anyhow::anyhow!(
+                    "Priority label '{}' is already the name of the {} level",
+                    label,
+                    PRIORITY_NAMES[level]
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_week_start(s: &str) -> Result<WeekStart> {
+    match s.to_lowercase().as_str() {
+        "monday" | "mon" => Ok(WeekStart::Monday),
+        "sunday" | "sun" => Ok(WeekStart::Sunday),
+        _ => Err(anyhow::anyhow!("Invalid week start '{}'. Use 'monday' or 'sunday'", s)),
+    }
+}
+
+/// A tag prefix must be a single symbol so it can't be mistaken for tag text
+fn parse_tag_prefix(s: &str) -> Result<String> {
+    let mut chars = s.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_alphanumeric() && !c.is_whitespace() && c != ',' => Ok(c.to_string()),
+        _ => Err(anyhow::anyhow!("Invalid tag prefix '{}'. Use a single symbol such as '#', '@' or '+'", s)),
+    }
+}
+
+/// Set one config field by its serialized name, parsing `value` the same way
+/// as the matching flag. Returns "key = value" (as JSON) for display.
+fn set_config_value(config: &mut ReminderConfig, key: &str, value: &str) -> Result<String> {
+    let key = key.trim().replace('-', "_");
+    let mut fields = serde_json::to_value(&*config)?;
+    let Some(object) = fields.as_object_mut() else {
+        return Err(anyhow::anyhow!("Config is not a JSON object"));
+    };
+    if !object.contains_key(&key) {
+        let mut known: Vec<&str> = object.keys().map(String::as_str).collect();
+        known.sort();
+        return Err(anyhow::anyhow!("Unknown config key '{}'. Valid keys: {}", key, known.join(", ")));
+    }
+
+    let text = value.trim();
+    let clears = matches!(text.to_lowercase().as_str(), "none" | "off" | "default");
+    let parsed = match key.as_str() {
+        "enabled" | "use_notify_send" | "use_wall" | "start_from_quiet_end" | "estimate_hint" | "remind_undated"
+        | "lowercase_projects" | "lowercase_tags" | "strict_projects" | "use_file_sink" | "file_sink_append" => {
+            parse_config_bool(text).map(serde_json::Value::Bool)
+        }
+        "interval_minutes" => parse_interval(text).map(|minutes| serde_json::json!(minutes)),
+        "week_start" => parse_week_start(text).map(|start| serde_json::json!(start)),
+        "priority_labels" => parse_priority_labels(text).map(|labels| serde_json::json!(labels)),
+        "tag_prefix" => parse_tag_prefix(text).map(serde_json::Value::String),
+        "wall_quiet_start_hour" | "wall_quiet_end_hour" => parse_config_number(text, 0, 23),
+        "daily_capacity_minutes" => parse_config_number(text, 1, u32::MAX),
+        "reminder_due_soon_hours" | "default_estimate_minutes" | "overdue_grace_minutes" => {
+            parse_config_number(text, 0, u32::MAX)
+        }
+        // Zero turns these off, like the matching flags
+        "daily_goal" | "skip_if_idle_minutes" if clears || text == "0" => Ok(serde_json::Value::Null),
+        "daily_goal" | "skip_if_idle_minutes" => parse_config_number(text, 1, u32::MAX),
+        // Text is taken verbatim, so "true" or "123" stays a string
+        "completion_sound" | "file_sink_path" if clears || text.is_empty() => Ok(serde_json::Value::Null),
+        "completion_sound" | "file_sink_path" => Ok(serde_json::json!(text)),
+        _ => Err(anyhow::anyhow!("this setting can only be changed with `todo edit-config`")),
+    }
+    .map_err(|err| anyhow::anyhow!("Invalid value '{}' for {}: {}", value, key, err))?;
+    object.insert(key.clone(), parsed.clone());
+
+    *config = serde_json::from_value(fields)
+        .map_err(|err| anyhow::anyhow!("Invalid value '{}' for {}: {}", value, key, err))?;
+    Ok(format!("{} = {}", key, parsed))
+}
+
+/// On/off value for `config set`
+fn parse_config_bool(s: &str) -> Result<bool> {
     match s.to_lowercase().as_str() {
-        "low" => Priority::Low,
-        "high" => Priority::High,
-        "critical" => Priority::Critical,
-        _ => Priority::Medium,
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(anyhow::anyhow!("use one of true, false, on, off, yes, no")),
+    }
+}
+
+/// Whole number between `min` and `max` for `config set`
+fn parse_config_number(s: &str, min: u32, max: u32) -> Result<serde_json::Value> {
+    match s.parse::<u32>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(serde_json::json!(n)),
+        _ => Err(anyhow::anyhow!("use a whole number from {} to {}", min, max)),
+    }
+}
+
+/// Parse an edited configuration, rejecting unknown keys and the values the
+/// matching `config` flags would refuse. Keys left out keep their defaults.
+fn parse_edited_config(text: &str) -> Result<ReminderConfig, String> {
+    let table: toml::Table = text.parse().map_err(|err| format!("Invalid TOML: {}", err))?;
+    let known = serde_json::to_value(ReminderConfig::default()).map_err(|err| err.to_string())?;
+    if let Some(key) = table.keys().find(|key| known.get(key.as_str()).is_none()) {
+        return Err(format!("Unknown config key '{}'", key));
+    }
+    let config: ReminderConfig = toml::from_str(text).map_err(|err| format!("Invalid value: {}", err))?;
+
+    if config.interval_minutes == 0 || config.interval_minutes > MAX_INTERVAL_MINUTES {
+        return Err(format!("interval_minutes must be between 1 and {}", MAX_INTERVAL_MINUTES));
+    }
+    if config.wall_quiet_start_hour > 23 || config.wall_quiet_end_hour > 23 {
+        return Err("Wall quiet hours must be between 0 and 23".to_string());
+    }
+    if config.daily_capacity_minutes == 0 {
+        return Err("daily_capacity_minutes must be above 0".to_string());
+    }
+    parse_tag_prefix(&config.tag_prefix).map_err(|err| err.to_string())?;
+    if let Some(labels) = &config.priority_labels {
+        validate_priority_labels(labels).map_err(|err| err.to_string())?;
     }
+    Ok(config)
 }
 
+/// The configuration as TOML for `edit-config`. TOML has no null, so unset
+/// optional settings are listed as comments to show they exist.
+fn config_to_toml(config: &ReminderConfig) -> Result<String> {
+    let mut text = toml::to_string(config)?;
+    let fields = serde_json::to_value(config)?;
+    let unset: Vec<&String> = fields
+        .as_object()
+        .map(|object| object.iter().filter(|(_, value)| value.is_null()).map(|(key, _)| key).collect())
+        .unwrap_or_default();
+    if !unset.is_empty() {
+        text.push_str("\n# Not set; add a line to set one:\n");
+        for key in unset {
+            text.push_str(&format!("# {} = ...\n", key));
+        }
+    }
+    Ok(text)
+}
+
+/// Prefix of the comment lines that carry a validation error into the editor
+const EDIT_ERROR_PREFIX: &str = "# ERROR: ";
+
+/// Put `message` at the top of `draft` as comments, replacing an earlier one
+fn annotate_edit_error(draft: &str, message: &str) -> String {
+    let mut text: String = message.lines().map(|line| format!("{}{}\n", EDIT_ERROR_PREFIX, line)).collect();
+    text.push_str(&strip_edit_error(draft));
+    text
+}
+
+fn strip_edit_error(draft: &str) -> String {
+    draft
+        .lines()
+        .filter(|line| !line.starts_with(EDIT_ERROR_PREFIX))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Settings that differ between two configurations, as (key, old, new) JSON
+fn config_changes(old: &ReminderConfig, new: &ReminderConfig) -> Vec<(String, String, String)> {
+    let (Ok(old), Ok(new)) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(key, value)| old.get(key.as_str()) != Some(value))
+        .map(|(key, value)| {
+            let before = old.get(key.as_str()).map(|v| v.to_string()).unwrap_or_default();
+            (key.clone(), before, value.to_string())
+        })
+        .collect()
+}
+
+/// Write `contents` to a file that must not exist yet (so a planted file or
+/// symlink is never followed), readable only by the user. A copy left by an
+/// earlier round of the same run is replaced.
+fn write_new_private_file(path: &std::path::Path, contents: &str) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    std::io::Write::write_all(&mut file, contents.as_bytes())?;
+    Ok(())
+}
+
+/// Open `path` in the user's editor ($VISUAL, then $EDITOR, then vi) and
+/// wait for it. The editor runs through the shell so "code --wait" works.
+fn run_editor(path: &std::path::Path) -> Result<bool> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let status = std::process::Command::new("sh")
+        .args(["-c", &format!("{} \"$1\"", editor), "sh"])
+        .arg(path)
+        .status()?;
+    Ok(status.success())
+}
+
+/// Shortest reminder interval that is not flagged as wall spam
+const MIN_WALL_INTERVAL_MINUTES: u32 = 15;
+
+/// Longest accepted reminder interval, in minutes (one week)
+const MAX_INTERVAL_MINUTES: u32 = 7 * 24 * 60;
+
 fn parse_interval(s: &str) -> Result<u32> {
     let s = s.trim().to_lowercase();
     
-    if s.ends_with('h') {
+    let minutes = if s.ends_with('h') {
         let hours: u32 = s.trim_end_matches('h').parse()
             .map_err(|_| anyhow::anyhow!("Invalid hours format"))?;
-        Ok(hours * 60)
+        hours.checked_mul(60)
+            .ok_or_else(|| anyhow::anyhow!("Interval of {} hours is too large", hours))?
     } else if s.ends_with('m') {
-        let minutes: u32 = s.trim_end_matches('m').parse()
-            .map_err(|_| anyhow::anyhow!("Invalid minutes format"))?;
-        Ok(minutes)
+        s.trim_end_matches('m').parse()
+            .map_err(|_| anyhow::anyhow!("Invalid minutes format"))?
     } else {
         // Assume minutes if no suffix
-        let minutes: u32 = s.parse()
-            .map_err(|_| anyhow::anyhow!("Invalid interval format. Use '2h' for hours or '30m' for minutes"))?;
-        Ok(minutes)
+        s.parse()
+            .map_err(|_| anyhow::anyhow!("Invalid interval format. Use '2h' for hours or '30m' for minutes"))?
+    };
+
+    if minutes == 0 {
+        return Err(anyhow::anyhow!("Interval must be at least 1 minute"));
     }
+    if minutes > MAX_INTERVAL_MINUTES {
+        return Err(anyhow::anyhow!(
+            "Interval of {} minutes is too long. The maximum is 1 week ({} minutes, or '168h')",
+            minutes,
+            MAX_INTERVAL_MINUTES
+        ));
+    }
+    Ok(minutes)
+}
+
+/// Parse a relative offset such as "30m", "2h", "1d" or "1w". Negative
+/// offsets and offsets beyond 100 years are rejected so date arithmetic on
+/// them cannot overflow.
+fn parse_relative_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let unit = s.chars().last()?;
+    let amount: i64 = s[..s.len() - unit.len_utf8()].parse().ok().filter(|n| *n >= 0)?;
+
+    let offset = match unit {
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }?;
+    (offset.num_days().abs() <= 36_500).then_some(offset)
 }
 
 fn parse_due_time(s: &str) -> Result<Option<DateTime<Utc>>> {
@@ -174,18 +729,14 @@ fn parse_due_time(s: &str) -> Result<Option<DateTime<Utc>>> {
         return Ok(None);
     }
 
-    // Parse relative time first
-    if s.ends_with('h') {
-        let hours: i64 = s.trim_end_matches('h').parse()?;
-        return Ok(Some(Utc::now() + Duration::hours(hours)));
-    }
-    if s.ends_with('d') {
-        let days: i64 = s.trim_end_matches('d').parse()?;
-        return Ok(Some(Utc::now() + Duration::days(days)));
+    // Explicitly clear the due date
+    if matches!(s.to_lowercase().as_str(), "none" | "clear" | "never") {
+        return Ok(None);
     }
-    if s.ends_with('w') {
-        let weeks: i64 = s.trim_end_matches('w').parse()?;
-        return Ok(Some(Utc::now() + Duration::weeks(weeks)));
+
+    // Parse relative time first
+    if let Some(offset) = parse_relative_duration(s) {
+        return Ok(Some(Utc::now() + offset));
     }
 
     // Parse absolute time
@@ -213,15 +764,169 @@ fn parse_due_time(s: &str) -> Result<Option<DateTime<Utc>>> {
         return Ok(Some(DateTime::from_naive_utc_and_offset(naive_datetime, Utc)));
     }
 
+    // 12-hour clock, alone (today) or after a date (e.g., "9pm", "2024-01-01 9:00 PM")
+    if let Some(due) = parse_12h_due(s, Utc::now().date_naive()) {
+        return Ok(Some(due));
+    }
+
     Err(anyhow::anyhow!("Cannot parse time format: {}", s))
 }
 
+/// Move a task's deadline by `offset`. Offsets stack on the existing due date
+/// (or start from `now` without one), so repeated defers add up.
+fn defer_task(task: &mut Task, offset: Duration, now: DateTime<Utc>) {
+    task.due_at = Some(task.due_at.unwrap_or(now) + offset);
+    // Whole days keep an all-day task on a day boundary
+    let remainder = offset.num_seconds() % Duration::days(1).num_seconds();
+    task.all_day &= remainder == 0;
+}
+
+/// Parse a `done --at` time. Relative durations count back from now
+/// ("2h" or "2h ago" is two hours ago); anything else parses like a due time.
+fn parse_completed_at(s: &str) -> Result<DateTime<Utc>> {
+    let trimmed = s.trim();
+    let relative = trimmed.strip_suffix("ago").map(str::trim).unwrap_or(trimmed);
+    if let Some(offset) = parse_relative_duration(relative) {
+        return Ok(Utc::now() - offset);
+    }
+    parse_due_time(trimmed)?.ok_or_else(|| anyhow::anyhow!("Completion time cannot be empty"))
+}
+
+/// Check a backdated completion time: not in the future, and not before the
+/// task was created
+fn validate_completed_at(at: DateTime<Utc>, task: &Task, now: DateTime<Utc>) -> Result<(), String> {
+    if at > now {
+        return Err(format!("Completion time {} is in the future", at.format("%Y-%m-%d %H:%M")));
+    }
+    if at < task.created_at {
+        return Err(format!(
+            "Completion time {} is before '{}' was created ({})",
+            at.format("%Y-%m-%d %H:%M"),
+            task.title,
+            task.created_at.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    Ok(())
+}
+
+/// Parse an am/pm due time, alone (on `today`) or after a YYYY-MM-DD date.
+/// Like the 24-hour forms it is UTC, so "9pm" and "21:00" are the same time.
+fn parse_12h_due(s: &str, today: NaiveDate) -> Option<DateTime<Utc>> {
+    let (date, time) = match parse_12h_time(s) {
+        Some(time) => (today, time),
+        None => {
+            let (date, time) = s.trim().split_once(' ')?;
+            (NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?, parse_12h_time(time)?)
+        }
+    };
+    Some(date.and_time(time).and_utc())
+}
+
+/// Parse an am/pm time such as "9pm", "11 am" or "9:30 PM"
+fn parse_12h_time(s: &str) -> Option<chrono::NaiveTime> {
+    let compact: String = s.split_whitespace().collect::<String>().to_uppercase();
+    if !(compact.ends_with("AM") || compact.ends_with("PM")) {
+        return None;
+    }
+    let (clock, meridiem) = compact.split_at(compact.len() - 2);
+    let clock = if clock.contains(':') { clock.to_string() } else { format!("{}:00", clock) };
+    chrono::NaiveTime::parse_from_str(&format!("{} {}", clock, meridiem), "%I:%M %p").ok()
+}
+
+/// Whether a due input names a whole day (YYYY-MM-DD) rather than a time
+fn is_date_only(s: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+}
+
+/// File in the current directory naming the default project for `add`
+const PROJECT_FILE: &str = ".todo-queue-project";
+
+/// Project for `add` when `--project` is not given: `TODO_QUEUE_PROJECT`,
+/// then the first line of `.todo-queue-project` in the current directory
+fn default_project() -> Option<String> {
+    let from_env = std::env::var("TODO_QUEUE_PROJECT").ok();
+    let from_file = || {
+        std::fs::read_to_string(PROJECT_FILE)
+            .ok()
+            .and_then(|contents| contents.lines().next().map(String::from))
+    };
+    from_env
+        .filter(|p| !p.trim().is_empty())
+        .or_else(from_file)
+        .filter(|p| !p.trim().is_empty())
+}
+
+/// Trim a project name and apply `lowercase_projects`, or `None` if nothing
+/// remains. With `strict_projects`, a name that matches an existing project
+/// only up to case is rejected with a message suggesting that spelling.
+fn normalize_project(name: &str, existing: &[String], config: &ReminderConfig) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let name = if config.lowercase_projects {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    };
+
+    if config.strict_projects && !existing.contains(&name) {
+        if let Some(spelling) = existing.iter().find(|p| p.to_lowercase() == name.to_lowercase()) {
+            return Err(format!("Project '{}' already exists as '{}'. Use --project \"{}\".", name, spelling, spelling));
+        }
+    }
+    Ok(Some(name))
+}
+
+/// Split comma-separated tags into the stored form: trimmed, without empties
+/// or duplicates (ignoring case), and lowercase with `lowercase_tags`. A
+/// leading tag prefix is dropped so "#home" round-trips as "home"; tags with
+/// the prefix or whitespace inside are rejected.
+fn normalize_tags(raw: &str, config: &ReminderConfig) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.split(',') {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix(config.tag_prefix.as_str()).unwrap_or(tag);
+        if tag.is_empty() {
+            continue;
+        }
+        if tag.contains(config.tag_prefix.as_str()) || tag.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(format!(
+                "Invalid tag '{}'. Tags cannot contain spaces or '{}'",
+                tag, config.tag_prefix
+            ));
+        }
+        let tag = if config.lowercase_tags {
+            tag.to_lowercase()
+        } else {
+            tag.to_string()
+        };
+        if !tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// Trim a title, or `None` if nothing but whitespace remains
+fn clean_title(title: &str) -> Option<String> {
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
 fn is_pure_numeric(s: &str) -> bool {
     // Validate string is numeric only
     !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
 }
 
 fn find_task_by_index_or_title(tasks: &[Task], target: &str) -> Option<(usize, i64)> {
+    let target = target.trim();
+
+    // `id:<n>` addresses a task by its stable database id
+    if let Some(id) = target.strip_prefix("id:").and_then(|id| id.trim().parse::<i64>().ok()) {
+        return tasks.iter().position(|t| t.id == id).map(|idx| (idx, id));
+    }
+
     // Parse as index first
     if let Ok(index) = target.parse::<usize>() {
         if index >= 1 && index <= tasks.len() {
@@ -239,122 +944,520 @@ fn find_task_by_index_or_title(tasks: &[Task], target: &str) -> Option<(usize, i
     None
 }
 
-fn get_db_path() -> PathBuf {
-    let mut path = dirs::home_dir().expect("Cannot determine home directory");
-    path.push(".todo-queue");
-    path.push("tasks.db");
-    path
+/// Selection weight for `todo pick`: the priority weight, optionally
+/// multiplied by how pressing the due date is. Tasks that are not
+/// actionable (such as blocked ones) are never picked.
+fn pick_weight(task: &Task, urgency: bool, config: &ReminderConfig) -> u32 {
+    if !task.is_actionable() {
+        return 0;
+    }
+    let weight = task.priority.weight() as u32;
+    if !urgency {
+        return weight;
+    }
+
+    match task.due_at {
+        _ if task.is_overdue(config) => weight * 3,
+        Some(due) if due.signed_duration_since(Utc::now()).num_hours() < 24 => weight * 2,
+        _ => weight,
+    }
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let db = Database::new(get_db_path())?;
+/// Order tasks for `todo forecast`: most urgent first (see `pick_weight`),
+/// then earliest due, keeping the list order for ties
+fn forecast_order(tasks: &mut [&Task], config: &ReminderConfig) {
+    tasks.sort_by_key(|t| (std::cmp::Reverse(pick_weight(t, true, config)), t.due_at.is_none(), t.due_at));
+}
 
-    match cli.command {
-        Commands::Add {
-            title,
-            description,
-            priority,
-            due,
-            project,
-            tags,
-            estimate,
-        } => {
-            // Check title is not numeric only
-            if is_pure_numeric(&title) {
-                println!("{} Task title cannot be pure numeric!", "⚠️".yellow());
-                println!("   Please use a meaningful name with letters or other characters.");
-                return Ok(());
+/// Day on which each task finishes when the estimates are worked through in
+/// order, `daily_minutes` a day starting with a full day on `start`. A task
+/// bigger than what is left of a day carries over into the following days.
+fn forecast_finish_dates(estimates: &[u32], start: NaiveDate, daily_minutes: u32) -> Vec<NaiveDate> {
+    let daily_minutes = daily_minutes.max(1);
+    let mut day = start;
+    let mut used = 0;
+    estimates
+        .iter()
+        .map(|&estimate| {
+            let mut remaining = estimate;
+            while remaining > daily_minutes - used {
+                remaining -= daily_minutes - used;
+                day = day.succ_opt().unwrap_or(day);
+                used = 0;
             }
+            used += remaining;
+            day
+        })
+        .collect()
+}
 
-            // Check duplicate task title
-            let tasks = db.list_tasks(false)?;
-            for task in &tasks {
-                if task.title.eq_ignore_ascii_case(&title) {
-                    println!("⚠️  Task '{}' already exists!", title);
-                    println!("   Use 'todo update \"{}\"' to modify it", title);
-                    return Ok(());
-                }
-            }
+/// A task as a JSON object with every stored field (times in RFC 3339) plus
+/// computed status fields for scripts
+fn task_json(task: &Task, config: &ReminderConfig) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(task)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("is_completed".into(), task.is_completed().into());
+        object.insert("is_overdue".into(), task.is_overdue(config).into());
+        object.insert("is_snoozed".into(), task.is_snoozed().into());
+        object.insert("urgency_score".into(), pick_weight(task, true, config).into());
+    }
+    Ok(value)
+}
 
-            let task = Task {
-                id: 0,
-                title,
-                description,
-                priority: parse_priority(&priority),
-                created_at: Utc::now(),
-                due_at: parse_due_time(&due.unwrap_or_default())?,
-                completed_at: None,
-                tags: tags
-                    .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
-                    .unwrap_or_default(),
-                project,
-                estimated_minutes: estimate,
-            };
+/// Ring the terminal bell, or start `sound` as a shell command without
+/// waiting for it; a sound that fails to play is not worth an error
+fn play_completion_sound(sound: &str) {
+    if sound.eq_ignore_ascii_case("bell") {
+        print!("\x07");
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+        return;
+    }
 
-            let id = db.add_task(&task)?;
-            let tasks = db.list_tasks(false)?;
-            let index = tasks.iter().position(|t| t.id == id).map(|i| i + 1).unwrap_or(0);
-            println!("✅ {} Task added (Index: {})", task.priority.as_str(), index);
-            println!("   {}", task.title.bold());
-        }
+    let _ = std::process::Command::new("sh")
+        .args(["-c", sound])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
 
-        Commands::List { completed, all, project } => {
-            let mut tasks = db.list_tasks(completed || all)?;
+/// A user-facing command failure, such as an unresolvable task target
+#[derive(Debug)]
+struct CommandError {
+    message: String,
+    code: i32,
+}
 
-            if let Some(proj) = project {
-                tasks.retain(|t| t.project.as_deref() == Some(proj.as_str()));
-            }
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
 
-            if completed {
-                ui::print_task_list(&tasks, "📋 All Tasks");
-            } else if all {
-                ui::print_task_list(&tasks, "📋 All Tasks (Including Completed)");
+impl std::error::Error for CommandError {}
+
+/// Exit code for errors that are not a `CommandError`
+const EXIT_FAILURE: i32 = 1;
+/// Exit code for commands that could not be carried out as requested
+const EXIT_COMMAND_FAILED: i32 = 2;
+
+/// Report a command failure: a yellow warning for humans, or an error that
+/// `main` serializes when `--json` is set
+fn warn(json: bool, message: impl Into<String>) -> Result<()> {
+    let message = message.into();
+    if json {
+        return Err(CommandError {
+            message,
+            code: EXIT_COMMAND_FAILED,
+        }
+        .into());
+    }
+    println!("{} {}", ui::glyph("⚠️").yellow(), message);
+    Ok(())
+}
+
+/// Name pending tasks that share a title; while they exist the database
+/// can't enforce unique titles
+fn print_duplicate_titles_notice(db: &Database) -> Result<()> {
+    let titles = db.duplicate_pending_titles()?;
+    if !titles.is_empty() {
+        let titles: Vec<String> = titles.iter().map(|t| format!("'{}'", t)).collect();
+        println!(
+            "{} Pending tasks share titles: {}. Rename or complete the duplicates so titles are enforced unique.",
+            ui::glyph("⚠️").yellow(),
+            titles.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Shared by `block` and `unblock`
+fn set_task_blocked(db: &Database, json: bool, target: &str, blocked: bool) -> Result<()> {
+    let tasks = db.list_tasks(false)?;
+    let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, target) else {
+        return warn(json, "Task not found. Use 'todo list' to see valid indices or titles.");
+    };
+
+    if db.set_blocked(task_id, blocked)? {
+        if blocked {
+            println!("{} Task blocked", ui::glyph("⏸️"));
+        } else {
+            println!("{} Task unblocked", ui::glyph("▶️"));
+        }
+    } else if blocked {
+        println!("{} Task is already blocked", ui::glyph("⏸️"));
+    } else {
+        println!("{} Task is not blocked", ui::glyph("▶️"));
+    }
+    println!("   {}", tasks[idx].title.bold());
+    Ok(())
+}
+
+/// Turn a local path into an absolute one (warning if it doesn't exist);
+/// URLs are kept as given
+fn resolve_attachment(reference: &str) -> String {
+    let reference = reference.trim();
+    if reference.contains("://") {
+        return reference.to_string();
+    }
+
+    let path = std::path::Path::new(reference);
+    match path.canonicalize() {
+        Ok(path) => path.display().to_string(),
+        Err(_) => {
+            println!("{} '{}' does not exist; attaching it anyway", ui::glyph("⚠️").yellow(), reference);
+            std::path::absolute(path)
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| reference.to_string())
+        }
+    }
+}
+
+/// Bulk commands touching more tasks than this ask before going ahead
+const BULK_CONFIRM_THRESHOLD: usize = 5;
+
+/// Ask a yes/no question on stdin; only a typed "yes" counts as confirmation
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} (type 'yes' to confirm): ", question);
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    Ok(input.trim().to_lowercase() == "yes")
+}
+
+fn get_db_path() -> PathBuf {
+    let mut path = dirs::home_dir().expect("Cannot determine home directory");
+    path.push(".todo-queue");
+    path.push("tasks.db");
+    path
+}
+
+/// Send diagnostics to stderr when `TODO_QUEUE_LOG` (or `RUST_LOG`) holds a
+/// filter such as "debug"; without either, nothing is logged
+fn init_logging() {
+    let filter = std::env::var("TODO_QUEUE_LOG").or_else(|_| std::env::var("RUST_LOG"));
+    if let Ok(filter) = filter {
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::new(filter))
+            .with_writer(std::io::stderr)
+            .init();
+    }
+}
+
+fn main() -> Result<()> {
+    init_logging();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Help and version output are not errors and keep clap's layout
+        Err(err) if err.use_stderr() && json_requested(std::env::args()) => {
+            eprintln!("{}", clap_error_json(&err));
+            std::process::exit(EXIT_COMMAND_FAILED);
+        }
+        Err(err) => err.exit(),
+    };
+    let json = cli.json;
+    let result = run(cli);
+
+    if json {
+        if let Err(err) = &result {
+            let code = err
+                .downcast_ref::<CommandError>()
+                .map(|e| e.code)
+                .unwrap_or(EXIT_FAILURE);
+            eprintln!("{}", serde_json::json!({ "error": format!("{:#}", err), "code": code }));
+            std::process::exit(code);
+        }
+    }
+
+    result
+}
+
+/// Whether `--json` appears among the options, for errors raised before the
+/// arguments could be parsed
+fn json_requested(args: impl Iterator<Item = String>) -> bool {
+    args.take_while(|arg| arg != "--").any(|arg| arg == "--json")
+}
+
+/// A command line error in the `{"error": ..., "code": ...}` shape of `--json`
+fn clap_error_json(err: &clap::Error) -> serde_json::Value {
+    let rendered = err.to_string();
+    let message = rendered.lines().next().unwrap_or_default();
+    let message = message.strip_prefix("error: ").unwrap_or(message);
+    serde_json::json!({ "error": message, "code": EXIT_COMMAND_FAILED })
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let json = cli.json;
+    ui::set_no_emoji(cli.no_emoji);
+    let db = Database::new(get_db_path())?;
+    let config = db.get_config()?;
+
+    match cli.command {
+        Commands::Add {
+            title,
+            description,
+            priority,
+            due,
+            project,
+            tags,
+            estimate,
+            label,
+            no_remind,
+            attachments,
+        } => {
+            // Surrounding whitespace is not part of the title
+            let Some(title) = clean_title(&title) else {
+                return warn(json, "Task title cannot be empty!");
+            };
+
+            // Check title is not numeric only
+            if is_pure_numeric(&title) {
+                warn(json, "Task title cannot be pure numeric!")?;
+                println!("   Please use a meaningful name with letters or other characters.");
+                return Ok(());
+            }
+
+            // Check duplicate task title
+            let tasks = db.list_tasks(false)?;
+            for task in &tasks {
+                if task.title.eq_ignore_ascii_case(&title) {
+                    warn(json, format!("Task '{}' already exists!", title))?;
+                    println!("   Use 'todo update \"{}\"' to modify it", title);
+                    return Ok(());
+                }
+            }
+
+            let tags = match tags.map(|t| normalize_tags(&t, &config)).transpose() {
+                Ok(tags) => tags.unwrap_or_default(),
+                Err(message) => return warn(json, message),
+            };
+
+            let project = match project.or_else(default_project) {
+                Some(name) => match normalize_project(&name, &db.distinct_projects()?, &config) {
+                    Ok(project) => project,
+                    Err(message) => return warn(json, message),
+                },
+                None => None,
+            };
+
+            let priority = match parse_priority(&priority, &config) {
+                Ok(priority) => priority,
+                Err(err) => return warn(json, err.to_string()),
+            };
+
+            let due = due.unwrap_or_default();
+            let task = Task {
+                id: 0,
+                title,
+                description,
+                priority,
+                created_at: Utc::now(),
+                due_at: parse_due_time(&due)?,
+                completed_at: None,
+                tags,
+                project,
+                estimated_minutes: estimate,
+                snoozed_until: None,
+                checklist: Vec::new(),
+                label,
+                all_day: is_date_only(&due),
+                reminders_enabled: !no_remind,
+                blocked: false,
+                attachments: attachments.iter().map(|a| resolve_attachment(a)).collect(),
+            };
+
+            let id = match db.add_task(&task) {
+                Err(err) if err.is::<DuplicateTitle>() => return warn(json, err.to_string()),
+                result => result?,
+            };
+            let tasks = db.list_tasks(false)?;
+            let index = tasks.iter().position(|t| t.id == id).map(|i| i + 1).unwrap_or(0);
+            println!("{} {} Task added (Index: {})", ui::glyph("✅"), ui::glyph(task.priority.as_str()), index);
+            println!("   {}", task.title.bold());
+            if !json {
+                print_duplicate_titles_notice(&db)?;
+            }
+        }
+
+        Commands::List {
+            completed,
+            all,
+            project,
+            estimate_min,
+            estimate_max,
+            blocked,
+            actionable,
+            format,
+            show_id,
+            porcelain,
+        } => {
+            let filter = TaskFilter {
+                include_completed: completed || all,
+                project,
+                estimate_min,
+                estimate_max,
+                blocked_only: blocked,
+                actionable_only: actionable,
+            };
+            let tasks = db.query_tasks(&filter)?;
+            if porcelain {
+                for task in &tasks {
+                    println!("{}", ui::porcelain_line(task));
+                }
+                return Ok(());
+            }
+
+            let title = if blocked {
+                format!("{} Blocked Tasks", ui::glyph("⏸️"))
+            } else if actionable {
+                format!("{} Actionable Tasks", ui::glyph("📋"))
+            } else if completed {
+                format!("{} All Tasks", ui::glyph("📋"))
+            } else if all {
+                format!("{} All Tasks (Including Completed)", ui::glyph("📋"))
             } else {
-                ui::print_task_list(&tasks, "📋 Pending Tasks");
+                format!("{} Pending Tasks", ui::glyph("📋"))
+            };
+            let filters = filter.describe();
+            if tasks.is_empty() && !filters.is_empty() {
+                ui::print_no_matches(&title, &filters, db.count_pending()?);
+                return Ok(());
+            }
+            match format {
+                ListFormat::Default => ui::print_task_list(&tasks, &title, show_id, &config),
+                ListFormat::Wide => ui::print_task_table(&tasks, &title, &ui::WIDE_COLUMNS, &config),
+            }
+            if !json {
+                print_duplicate_titles_notice(&db)?;
             }
         }
 
-        Commands::Next => {
+        Commands::Next { count_only } => {
+            if count_only {
+                println!("{}", db.count_pending()?);
+                return Ok(());
+            }
+
             if let Some(task) = db.get_next_task()? {
-                println!("\n{}", "🎯 Next Task".bold().underline());
-                println!("{}", "=".repeat(50));
-                println!("\n{}", ui::format_task(&task, false));
+                println!("\n{}", format!("{} Next Task", ui::glyph("🎯")).bold().underline());
+                println!("{}", ui::rule("=", 50));
+                println!("\n{}", ui::format_task(&task, false, &config));
 
-                if task.is_overdue() {
-                    println!("\n⚠️  This task is overdue!",);
+                if task.is_overdue(&config) {
+                    println!("\n{}  This task is overdue!", ui::glyph("⚠️"));
                 }
                 println!("\nUse {} to complete this task", "todo done".cyan());
             } else {
-                println!("\n{} No pending tasks, enjoy your life! 🎉", "✨".bold());
+                println!("\n{} No pending tasks, enjoy your life! {}", ui::glyph("✨").bold(), ui::glyph("🎉"));
             }
         }
 
-        Commands::Done { target } => {
+        Commands::Pick { urgency, seed } => {
+            let tasks = db.list_tasks(false)?;
+            if tasks.is_empty() {
+                println!("\n{} No pending tasks, enjoy your life! {}", ui::glyph("✨").bold(), ui::glyph("🎉"));
+                return Ok(());
+            }
+            if !tasks.iter().any(Task::is_actionable) {
+                return warn(json, "Every pending task is blocked. Use 'todo unblock' when one can move again.");
+            }
+
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            let weights = WeightedIndex::new(tasks.iter().map(|t| pick_weight(t, urgency, &config)))?;
+            let idx = weights.sample(&mut rng);
+            let task = &tasks[idx];
+
+            println!("\n{}", format!("{} Picked For You", ui::glyph("🎲")).bold().underline());
+            println!("{}", ui::rule("=", 50));
+            println!("\n{}", ui::format_task(task, false, &config));
+            println!("\nUse {} to complete this task", format!("todo done {}", idx + 1).cyan());
+        }
+
+        Commands::Done { target: _, project: Some(project), at } => {
+            let at = at.as_deref().map(parse_completed_at).transpose()?;
+            let tasks = db.query_tasks(&TaskFilter {
+                project: Some(project.clone()),
+                ..TaskFilter::default()
+            })?;
+            if tasks.is_empty() {
+                return warn(json, format!("No pending tasks in project '{}'", project));
+            }
+            if let Some(at) = at {
+                let now = Utc::now();
+                if let Some(err) = tasks.iter().find_map(|t| validate_completed_at(at, t, now).err()) {
+                    return warn(json, err);
+                }
+            }
+            if tasks.len() > BULK_CONFIRM_THRESHOLD
+                && !confirm(&format!("Complete all {} pending tasks in '{}'?", tasks.len(), project))?
+            {
+                println!("\n{} Cancelled", ui::glyph("❌"));
+                return Ok(());
+            }
+
+            let ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+            let completed = db.complete_tasks(&ids, at)?;
+            println!(
+                "{} Completed {} task{} in {}",
+                ui::glyph("✅"),
+                completed,
+                if completed == 1 { "" } else { "s" },
+                project.cyan()
+            );
+            for task in &tasks {
+                println!("   {}", task.title.bold());
+            }
+            if let Some(sound) = config.completion_sound.as_ref().filter(|_| completed > 0) {
+                play_completion_sound(sound);
+            }
+        }
+
+        Commands::Done { target, project: None, at } => {
+            let at = at.as_deref().map(parse_completed_at).transpose()?;
             let tasks = db.list_tasks(false)?;
             let task_id = if let Some(ref t) = target {
                 if let Some((_, id)) = find_task_by_index_or_title(&tasks, t) {
                     id
+                } else if t.trim().starts_with("id:") {
+                    // A stale stable id must not fall back to the next task
+                    return warn(json, "Task not found or already completed");
                 } else if let Some(task) = db.get_next_task()? {
                     task.id
                 } else {
-                    println!("{} No pending tasks", "⚠️".yellow());
-                    return Ok(());
+                    return warn(json, "No pending tasks");
                 }
             } else if let Some(task) = db.get_next_task()? {
                 task.id
             } else {
-                println!("{} No pending tasks", "⚠️".yellow());
-                return Ok(());
+                return warn(json, "No pending tasks");
             };
 
-            if db.complete_task(task_id)? {
+            if let Some(at) = at {
                 if let Some(task) = db.get_task(task_id)? {
-                    println!("✅ Task completed!");
+                    if let Err(err) = validate_completed_at(at, &task, Utc::now()) {
+                        return warn(json, err);
+                    }
+                }
+            }
+
+            if db.complete_task(task_id, at)? {
+                if let Some(task) = db.get_task(task_id)? {
+                    println!("{} Task completed!", ui::glyph("✅"));
                     println!("   {}", task.title.bold());
+                    if let Some(summary) = ui::estimate_summary(&task) {
+                        println!("   {}  {}", ui::glyph("⏱️"), summary.dimmed());
+                    }
+                }
+                if let Some(sound) = &config.completion_sound {
+                    play_completion_sound(sound);
                 }
             } else {
-                println!("{} Task not found or already completed", "⚠️".yellow());
+                warn(json, "Task not found or already completed")?;
             }
         }
 
@@ -362,46 +1465,56 @@ fn main() -> Result<()> {
             let tasks = db.list_tasks(false)?;
             if let Some((_, task_id)) = find_task_by_index_or_title(&tasks, &target) {
                 if db.delete_task(task_id)? {
-                    println!("🗑️  Task deleted permanently");
+                    println!("{}  Task deleted permanently", ui::glyph("🗑️"));
                 } else {
-                    println!("{} Failed to delete task", "⚠️".yellow());
+                    warn(json, "Failed to delete task")?;
                 }
             } else {
-                println!("{} Task not found. Use 'todo list' to see valid indices or titles.", "⚠️".yellow());
+                warn(json, "Task not found. Use 'todo list' to see valid indices or titles.")?;
             }
         }
 
         Commands::Clear => {
+            let cleared: Vec<Task> = db
+                .list_tasks(true)?
+                .into_iter()
+                .filter(|t| t.is_completed())
+                .collect();
+            for task in &cleared {
+                println!("{}", ui::format_task_compact(task));
+            }
+
             let count = db.clear_completed()?;
-            println!("🧹 Cleared {} completed tasks", count);
+            if !cleared.is_empty() {
+                println!();
+            }
+            println!("{} Cleared {} completed tasks", ui::glyph("🧹"), count);
         }
 
-        Commands::Reset => {
+        Commands::Reset { keep_ids } => {
             // Display current task count
             let tasks = db.list_tasks(true)?;
             let total = tasks.len();
             let completed = tasks.iter().filter(|t| t.is_completed()).count();
             let pending = total - completed;
 
-            println!("\n{}", "⚠️  WARNING: This will delete ALL tasks!".bold().red());
-            println!("{}", "=".repeat(50));
+            println!("\n{}", format!("{}  WARNING: This will delete ALL tasks!", ui::glyph("⚠️")).bold().red());
+            println!("{}", ui::rule("=", 50));
             println!("Total tasks: {}", total);
             println!("  - Pending: {}", pending);
             println!("  - Completed: {}", completed);
             println!();
 
-            // Ask for confirmation
-            print!("Are you sure you want to delete ALL tasks? (type 'yes' to confirm): ");
-            std::io::Write::flush(&mut std::io::stdout())?;
-
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-
-            if input.trim().to_lowercase() == "yes" {
-                let count = db.reset_all()?;
-                println!("\n✅ Deleted {} tasks from database", count);
+            if confirm("Are you sure you want to delete ALL tasks?")? {
+                let count = db.reset_all(!keep_ids)?;
+                println!("\n{} Deleted {} tasks from database", ui::glyph("✅"), count);
+                if keep_ids {
+                    println!("   New task ids continue from the previous highest id");
+                } else {
+                    println!("   New task ids start again at 1");
+                }
             } else {
-                println!("\n❌ Reset cancelled");
+                println!("\n{} Reset cancelled", ui::glyph("❌"));
             }
         }
 
@@ -409,21 +1522,97 @@ fn main() -> Result<()> {
             let tasks = db.list_tasks(true)?;
             if let Some((idx, _)) = find_task_by_index_or_title(&tasks, &target) {
                 let task = &tasks[idx];
-                println!("\n{}", "📝 Task Details".bold().underline());
-                println!("{}", "=".repeat(50));
-                println!("\n{}", ui::format_task(task, true));
-                println!("\nCreated: {}", task.created_at.format("%Y-%m-%d %H:%M:%S"));
-                if let Some(due) = task.due_at {
-                    println!("Due: {}", due.format("%Y-%m-%d %H:%M:%S"));
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&task_json(task, &config)?)?);
+                    return Ok(());
+                }
+                println!("\n{}", format!("{} Task Details", ui::glyph("📝")).bold().underline());
+                println!("{}", ui::rule("=", 50));
+                println!("\n{}", ui::format_task(task, true, &config));
+                println!("\nPriority: {} {}", ui::glyph(task.priority.as_str()), task.priority.label(&config));
+                let now = Utc::now();
+                println!(
+                    "Created: {} ({})",
+                    task.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    ui::format_age(task.created_at, now)
+                );
+                if task.created_at > now {
+                    println!(
+                        "{} Creation time is in the future; the system clock was probably wrong when this task was added",
+                        ui::glyph("⚠️").yellow()
+                    );
+                }
+                if task.due_at.is_some() {
+                    println!("Due: {}", ui::format_due(task));
                 }
                 if let Some(completed) = task.completed_at {
                     println!("Completed: {}", completed.format("%Y-%m-%d %H:%M:%S"));
                 }
+                if !task.checklist.is_empty() {
+                    println!("\n{}", ui::format_checklist(task));
+                }
+                if !task.attachments.is_empty() {
+                    println!("\n{}", ui::format_attachments(task));
+                }
+            } else {
+                warn(json, "Task not found. Use 'todo list --all' to see all valid indices or titles.")?;
+            }
+        }
+
+        Commands::Attach { target, reference } => {
+            let tasks = db.list_tasks(true)?;
+            let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, &target) else {
+                return warn(json, "Task not found. Use 'todo list --all' to see all valid indices or titles.");
+            };
+            let reference = resolve_attachment(&reference);
+            let mut attachments = tasks[idx].attachments.clone();
+            if attachments.contains(&reference) {
+                return warn(json, format!("'{}' is already attached", reference));
+            }
+            attachments.push(reference.clone());
+
+            if db.set_attachments(task_id, &attachments)? {
+                println!("{} Attached {}", ui::glyph("📎"), reference);
+                println!("   {}", tasks[idx].title.bold());
             } else {
-                println!("{} Task not found. Use 'todo list --all' to see all valid indices or titles.", "⚠️".yellow());
+                warn(json, "Failed to attach to task")?;
             }
         }
 
+        Commands::Check { target, action } => {
+            let tasks = db.list_tasks(false)?;
+            let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, &target) else {
+                return warn(json, "Task not found. Use 'todo list' to see valid indices or titles.");
+            };
+            let mut checklist = tasks[idx].checklist.clone();
+
+            match action {
+                CheckAction::Add { text } => {
+                    println!("{} Added step {}: {}", ui::glyph("➕"), checklist.len() + 1, text);
+                    checklist.push(ChecklistItem { text, done: false });
+                }
+                CheckAction::Toggle { step } => {
+                    let Some(item) = step.checked_sub(1).and_then(|i| checklist.get_mut(i)) else {
+                        return warn(json, format!("No step {}. Use 'todo show' to see the checklist.", step));
+                    };
+                    item.done = !item.done;
+                    let mark = if item.done { "[x]" } else { "[ ]" };
+                    println!("{} {}. {}", mark, step, item.text);
+                }
+                CheckAction::Remove { step } => {
+                    if step == 0 || step > checklist.len() {
+                        return warn(json, format!("No step {}. Use 'todo show' to see the checklist.", step));
+                    }
+                    let item = checklist.remove(step - 1);
+                    println!("{} Removed step {}: {}", ui::glyph("➖"), step, item.text);
+                }
+            }
+
+            db.set_checklist(task_id, &checklist)?;
+            let done = checklist.iter().filter(|item| item.done).count();
+            println!("   {} ({}/{} done)", tasks[idx].title.bold(), done, checklist.len());
+        }
+
         Commands::Update {
             target,
             title,
@@ -433,51 +1622,350 @@ fn main() -> Result<()> {
             project,
             tags,
             estimate,
+            label,
+            no_remind,
+            remind,
         } => {
             let tasks = db.list_tasks(false)?;
             if let Some((_, task_id)) = find_task_by_index_or_title(&tasks, &target) {
                 if let Some(mut task) = db.get_task(task_id)? {
                     // Update only provided fields
                     if let Some(new_title) = title {
+                        let Some(new_title) = clean_title(&new_title) else {
+                            return warn(json, "Task title cannot be empty!");
+                        };
                         task.title = new_title;
                     }
                     if let Some(new_description) = description {
                         task.description = Some(new_description);
                     }
                     if let Some(new_priority) = priority {
-                        task.priority = parse_priority(&new_priority);
+                        task.priority = match parse_priority(&new_priority, &config) {
+                            Ok(priority) => priority,
+                            Err(err) => return warn(json, err.to_string()),
+                        };
                     }
                     if let Some(new_due) = due {
                         task.due_at = parse_due_time(&new_due)?;
+                        task.all_day = is_date_only(&new_due);
                     }
                     if let Some(new_project) = project {
-                        task.project = Some(new_project);
+                        task.project = match normalize_project(&new_project, &db.distinct_projects()?, &config) {
+                            Ok(project) => project,
+                            Err(message) => return warn(json, message),
+                        };
                     }
                     if let Some(new_tags) = tags {
-                        task.tags = new_tags.split(',').map(|s| s.trim().to_string()).collect();
+                        task.tags = match normalize_tags(&new_tags, &config) {
+                            Ok(tags) => tags,
+                            Err(message) => return warn(json, message),
+                        };
                     }
                     if let Some(new_estimate) = estimate {
                         task.estimated_minutes = Some(new_estimate);
                     }
+                    if let Some(new_label) = label {
+                        task.label = match new_label.to_lowercase().as_str() {
+                            "none" | "clear" | "" => None,
+                            _ => Some(new_label),
+                        };
+                    }
+                    if no_remind || remind {
+                        task.reminders_enabled = remind;
+                    }
 
-                    if db.update_task(task_id, &task)? {
-                        println!("✅ Task updated");
+                    let updated = match db.update_task(task_id, &task) {
+                        Err(err) if err.is::<DuplicateTitle>() => return warn(json, err.to_string()),
+                        result => result?,
+                    };
+                    if updated {
+                        println!("{} Task updated", ui::glyph("✅"));
                         println!("   {}", task.title.bold());
                     } else {
-                        println!("{} Failed to update task", "⚠️".yellow());
+                        warn(json, "Failed to update task")?;
+                    }
+                }
+            } else {
+                warn(json, "Task not found. Use 'todo list' to see valid indices or titles.")?;
+            }
+        }
+
+        Commands::Rename { target, new_title } => {
+            let tasks = db.list_tasks(false)?;
+            let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, &target) else {
+                return warn(json, "Task not found. Use 'todo list' to see valid indices or titles.");
+            };
+            let Some(new_title) = clean_title(&new_title) else {
+                return warn(json, "Task title cannot be empty!");
+            };
+            if is_pure_numeric(&new_title) {
+                warn(json, "Task title cannot be pure numeric!")?;
+                println!("   Please use a meaningful name with letters or other characters.");
+                return Ok(());
+            }
+
+            let mut task = tasks[idx].clone();
+            let old_title = std::mem::replace(&mut task.title, new_title);
+            let updated = match db.update_task(task_id, &task) {
+                Err(err) if err.is::<DuplicateTitle>() => return warn(json, err.to_string()),
+                result => result?,
+            };
+            if updated {
+                println!("{} Task renamed", ui::glyph("✅"));
+                println!("   {} → {}", old_title.dimmed(), task.title.bold());
+            } else {
+                warn(json, "Failed to rename task")?;
+            }
+        }
+
+        Commands::Defer { target, duration } => {
+            let Some(offset) = parse_relative_duration(&duration) else {
+                return warn(json, format!("Invalid duration '{}'. Use e.g. '30m', '2h', '1d' or '1w'.", duration));
+            };
+
+            let tasks = db.list_tasks(false)?;
+            if let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, &target) {
+                let mut task = tasks[idx].clone();
+                defer_task(&mut task, offset, Utc::now());
+
+                if db.update_task(task_id, &task)? {
+                    println!("{} Task deferred to {}", ui::glyph("⏩"), ui::format_due(&task));
+                    println!("   {}", task.title.bold());
+                } else {
+                    warn(json, "Failed to defer task")?;
+                }
+            } else {
+                warn(json, "Task not found. Use 'todo list' to see valid indices or titles.")?;
+            }
+        }
+
+        Commands::Bump {
+            target,
+            direction,
+            project,
+            to,
+        } => {
+            if let (Some(project), Some(to)) = (project, to) {
+                let priority = match parse_priority(&to, &config) {
+                    Ok(priority) => priority,
+                    Err(err) => return warn(json, err.to_string()),
+                };
+                let ids: Vec<i64> = db
+                    .query_tasks(&TaskFilter {
+                        project: Some(project.clone()),
+                        ..TaskFilter::default()
+                    })?
+                    .iter()
+                    .map(|t| t.id)
+                    .collect();
+                if ids.is_empty() {
+                    return warn(json, format!("No pending tasks in project '{}'", project));
+                }
+
+                let changed = db.set_priorities(&ids, &priority)?;
+                println!(
+                    "{} Set {} of {} tasks in {} to {} {}",
+                    ui::glyph("✅"),
+                    changed,
+                    ids.len(),
+                    project.cyan(),
+                    ui::glyph(priority.as_str()),
+                    priority.label(&config)
+                );
+                return Ok(());
+            }
+
+            let (Some(target), Some(direction)) = (target, direction) else {
+                return warn(json, "Use 'todo bump <INDEX_OR_TITLE> up/down' or 'todo bump --project <name> --to <priority>'");
+            };
+            let step = match direction.to_lowercase().as_str() {
+                "up" => 1,
+                "down" => -1,
+                _ => return warn(json, format!("Invalid direction '{}'. Use 'up' or 'down'.", direction)),
+            };
+
+            let tasks = db.list_tasks(false)?;
+            let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, &target) else {
+                return warn(json, "Task not found. Use 'todo list' to see valid indices or titles.");
+            };
+            let mut task = tasks[idx].clone();
+            let old = task.priority.clone();
+            task.priority = Priority::from_weight(old.weight() as i32 + step);
+
+            if task.priority == old {
+                println!("Priority is already {} {}", ui::glyph(old.as_str()), old.label(&config));
+            } else if db.update_task(task_id, &task)? {
+                println!(
+                    "{} Priority {} {} → {} {}",
+                    ui::glyph("✅"),
+                    ui::glyph(old.as_str()),
+                    old.label(&config),
+                    ui::glyph(task.priority.as_str()),
+                    task.priority.label(&config)
+                );
+                println!("   {}", task.title.bold());
+            } else {
+                warn(json, "Failed to update task")?;
+            }
+        }
+
+        Commands::Swap { first, second } => {
+            let tasks = db.list_tasks(false)?;
+            let (Some((first_idx, first_id)), Some((second_idx, second_id))) = (
+                find_task_by_index_or_title(&tasks, &first),
+                find_task_by_index_or_title(&tasks, &second),
+            ) else {
+                return warn(json, "Task not found. Use 'todo list' to see valid indices or titles.");
+            };
+            if first_id == second_id {
+                return warn(json, "Cannot swap a task with itself");
+            }
+
+            db.swap_tasks(first_id, second_id)?;
+            println!("{} Swapped tasks", ui::glyph("🔀"));
+            println!("   {}", tasks[first_idx].title.bold());
+            println!("   {}", tasks[second_idx].title.bold());
+
+            if tasks[first_idx].priority != tasks[second_idx].priority {
+                println!("\nNote: tasks are ordered by priority first, so only their order within each priority changed.");
+            }
+        }
+
+        Commands::Block { target } => set_task_blocked(&db, json, &target, true)?,
+
+        Commands::Unblock { target } => set_task_blocked(&db, json, &target, false)?,
+
+        Commands::Snooze { target, duration } => {
+            let until = if matches!(duration.to_lowercase().as_str(), "none" | "clear" | "off") {
+                None
+            } else if let Some(offset) = parse_relative_duration(&duration) {
+                Some(Utc::now() + offset)
+            } else {
+                return warn(json, format!("Invalid duration '{}'. Use e.g. '30m', '2h', '1d' or 'none'.", duration));
+            };
+
+            let tasks = db.list_tasks(false)?;
+            if let Some((idx, task_id)) = find_task_by_index_or_title(&tasks, &target) {
+                if db.snooze_task(task_id, until)? {
+                    match until {
+                        Some(until) => println!("{} Reminders snoozed until {}", ui::glyph("💤"), until.format("%Y-%m-%d %H:%M")),
+                        None => println!("{} Reminders resumed", ui::glyph("🔔")),
                     }
+                    println!("   {}", tasks[idx].title.bold());
+                } else {
+                    warn(json, "Failed to snooze task")?;
                 }
             } else {
-                println!("{} Task not found. Use 'todo list' to see valid indices or titles.", "⚠️".yellow());
+                warn(json, "Task not found. Use 'todo list' to see valid indices or titles.")?;
             }
         }
 
-        Commands::Remind => {
-            let config = db.get_config()?;
-            reminders::check_reminders(&config)?;
+        Commands::Forecast => {
+            let tasks = db.list_tasks(false)?;
+            let mut queue: Vec<&Task> = tasks.iter().filter(|t| t.is_actionable()).collect();
+            forecast_order(&mut queue, &config);
+
+            let estimates: Vec<u32> = queue
+                .iter()
+                .map(|t| t.estimated_minutes.unwrap_or(config.default_estimate_minutes))
+                .collect();
+            let today = chrono::Local::now().date_naive();
+            let finishes = forecast_finish_dates(&estimates, today, config.daily_capacity_minutes);
+
+            let rows: Vec<ui::ForecastRow> = queue
+                .iter()
+                .zip(estimates.iter().zip(finishes))
+                .map(|(task, (&estimate, finish))| ui::ForecastRow {
+                    // Number tasks as `todo list` does, so they can be addressed
+                    index: tasks.iter().position(|t| t.id == task.id).unwrap_or(0) + 1,
+                    task,
+                    estimate,
+                    defaulted: task.estimated_minutes.is_none(),
+                    finish,
+                })
+                .collect();
+            ui::print_forecast(&rows, &config);
+        }
+
+        Commands::Top { interval } => {
+            if !std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+                return warn(json, "'todo top' needs an interactive terminal");
+            }
+            top::run(&get_db_path(), std::time::Duration::from_secs(interval))?;
+        }
+
+        Commands::Remind { test } => {
+            if test {
+                if !config.enabled {
+                    println!("{} Reminders are disabled; the timer will not send anything", ui::glyph("⚠️").yellow());
+                }
+                println!("\n{}", format!("{} Reminder Backend Test", ui::glyph("🧪")).bold().underline());
+                for (backend, ok) in reminders::send_test_reminder(&config) {
+                    let status = if ok {
+                        format!("{} delivered", ui::glyph("✅")).green()
+                    } else {
+                        format!("{} failed", ui::glyph("❌")).red()
+                    };
+                    println!("  {}: {}", backend, status);
+                }
+            } else {
+                reminders::check_reminders(&config)?;
+            }
+        }
+
+        Commands::EditConfig => {
+            // Kept in the private data directory rather than a shared temp dir
+            let path = get_db_path().with_file_name(format!("config-edit-{}.toml", std::process::id()));
+            let mut draft = config_to_toml(&config)?;
+
+            // Re-open the user's own text after a mistake, so no edits are
+            // lost; the error is shown at the top of the file
+            let edited = loop {
+                write_new_private_file(&path, &draft)?;
+                if !run_editor(&path)? {
+                    let _ = std::fs::remove_file(&path);
+                    return warn(json, "Editor exited with an error; configuration unchanged");
+                }
+                draft = strip_edit_error(&std::fs::read_to_string(&path)?);
+                match parse_edited_config(&draft) {
+                    Ok(edited) => break edited,
+                    Err(message) => {
+                        println!("{} {}", ui::glyph("❌"), message);
+                        draft = annotate_edit_error(&draft, &message);
+                        if !confirm("Edit again?")? {
+                            let _ = std::fs::remove_file(&path);
+                            println!("\n{} Configuration unchanged", ui::glyph("❌"));
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+            let _ = std::fs::remove_file(&path);
+
+            let changes = config_changes(&config, &edited);
+            if changes.is_empty() {
+                println!("{} No changes", ui::glyph("✨"));
+                return Ok(());
+            }
+            println!("\n{}", format!("{} Configuration Changes", ui::glyph("🔧")).bold());
+            for (key, before, after) in &changes {
+                println!("  {}: {} -> {}", key.cyan(), before.red(), after.green());
+            }
+            if !confirm("\nSave these changes?")? {
+                println!("\n{} Configuration unchanged", ui::glyph("❌"));
+                return Ok(());
+            }
+
+            db.save_config(&edited)?;
+            println!("\n{} Saved {} change{}", ui::glyph("✅"), changes.len(), if changes.len() == 1 { "" } else { "s" });
+            println!();
+            println!("{}  To apply changes, run the following commands:", ui::glyph("⚠️"));
+            println!("   1. systemctl --user daemon-reload");
+            println!("   2. systemctl --user restart todo-queue.timer");
         }
 
         Commands::Config {
+            action,
             enabled,
             interval,
             notify,
@@ -485,46 +1973,81 @@ fn main() -> Result<()> {
             wall_quiet_start,
             wall_quiet_end,
             start_from_quiet_end,
+            week_start,
+            priority_labels,
+            due_soon_hours,
+            daily_goal,
+            daily_capacity,
+            default_estimate,
+            skip_if_idle,
+            tag_prefix,
+            estimate_hint,
+            completion_sound,
+            remind_undated,
+            file_sink,
+            file_sink_path,
+            file_sink_append,
+            overdue_grace,
+            lowercase_projects,
+            lowercase_tags,
+            strict_projects,
             show,
+            reset,
         } => {
-            let mut config = db.get_config()?;
+            let mut config = config;
             let mut changed = false;
 
-            // Show current configuration
-            if show || (enabled.is_none() && interval.is_none() && notify.is_none() && wall.is_none() && wall_quiet_start.is_none() && wall_quiet_end.is_none() && start_from_quiet_end.is_none()) {
-                println!("\n{}", "🔧 Current Reminder Configuration".bold().underline());
-                println!("{}", "═".repeat(50));
-                println!("  Enabled: {}", if config.enabled { "✅ Yes" } else { "❌ No" });
-                
-                // Format interval
-                let hours = config.interval_minutes / 60;
-                let mins = config.interval_minutes % 60;
-                if hours > 0 && mins > 0 {
-                    println!("  Interval: {}h {}m", hours, mins);
-                } else if hours > 0 {
-                    println!("  Interval: {}h", hours);
-                } else {
-                    println!("  Interval: {}m", mins);
+            if reset {
+                if !confirm("Restore all reminder settings to their defaults?")? {
+                    println!("\n{} Config reset cancelled", ui::glyph("❌"));
+                    return Ok(());
                 }
-                
-                println!("  Desktop Notifications: {}", if config.use_notify_send { "✅ Yes" } else { "❌ No" });
-                println!("  Terminal Wall Messages: {}", if config.use_wall { "✅ Yes" } else { "❌ No" });
-                if config.use_wall {
-                    println!("  Wall Quiet Hours: {}:00 - {}:00 (no wall messages)", 
-                             config.wall_quiet_start_hour, config.wall_quiet_end_hour);
-                    if config.start_from_quiet_end {
-                        println!("  Start Time: Reminders start from quiet-end time");
-                    }
+                let config = ReminderConfig::default();
+                db.save_config(&config)?;
+                println!("\n{} Configuration restored to defaults", ui::glyph("✅"));
+                ui::print_config(&config);
+                return Ok(());
+            }
+
+            if let Some(ConfigAction::Set { key, value }) = action {
+                let setting = set_config_value(&mut config, &key, &value)?;
+                changed = true;
+                println!("{} Set {}", ui::glyph("✅"), setting);
+            } else if show || (enabled.is_none() && interval.is_none() && notify.is_none() && wall.is_none() && wall_quiet_start.is_none() && wall_quiet_end.is_none() && start_from_quiet_end.is_none() && week_start.is_none() && priority_labels.is_none() && due_soon_hours.is_none() && daily_goal.is_none() && daily_capacity.is_none() && default_estimate.is_none() && skip_if_idle.is_none() && tag_prefix.is_none() && estimate_hint.is_none() && completion_sound.is_none() && remind_undated.is_none() && file_sink.is_none() && file_sink_path.is_none() && file_sink_append.is_none() && overdue_grace.is_none() && lowercase_projects.is_none() && strict_projects.is_none() && lowercase_tags.is_none()) {
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&config)?);
+                    return Ok(());
                 }
+                ui::print_config(&config);
                 println!();
                 println!("To change configuration, use:");
                 println!("  {} --enabled true/false", "todo config".cyan());
                 println!("  {} --interval <time> (e.g., '2h', '30m', '60')", "todo config".cyan());
                 println!("  {} --notify true/false", "todo config".cyan());
                 println!("  {} --wall true/false", "todo config".cyan());
+                println!("  {} --file-sink true/false", "todo config".cyan());
+                println!("  {} --file-sink-path <path> or default", "todo config".cyan());
+                println!("  {} --file-sink-append true/false", "todo config".cyan());
                 println!("  {} --wall-quiet-start <hour> (0-23)", "todo config".cyan());
                 println!("  {} --wall-quiet-end <hour> (0-23)", "todo config".cyan());
                 println!("  {} --start-from-quiet-end true/false", "todo config".cyan());
+                println!("  {} --due-soon-hours <hours>", "todo config".cyan());
+                println!("  {} --remind-undated true/false", "todo config".cyan());
+                println!("  {} --skip-if-idle <minutes> (0 to turn off)", "todo config".cyan());
+                println!("  {} --overdue-grace <minutes>", "todo config".cyan());
+                println!("  {} --week-start monday/sunday", "todo config".cyan());
+                println!("  {} --daily-goal <tasks> (0 to turn off)", "todo config".cyan());
+                println!("  {} --daily-capacity <minutes>", "todo config".cyan());
+                println!("  {} --default-estimate <minutes>", "todo config".cyan());
+                println!("  {} --priority-labels <low,medium,high,critical> or default", "todo config".cyan());
+                println!("  {} --tag-prefix <char> (e.g., '#', '@', '+')", "todo config".cyan());
+                println!("  {} --lowercase-projects true/false", "todo config".cyan());
+                println!("  {} --strict-projects true/false", "todo config".cyan());
+                println!("  {} --lowercase-tags true/false", "todo config".cyan());
+                println!("  {} --estimate-hint true/false", "todo config".cyan());
+                println!("  {} --completion-sound bell/<command>/none", "todo config".cyan());
+                println!("  {} <key> <value> (any setting, see --json)", "todo config set".cyan());
+                println!("  {} --reset", "todo config".cyan());
                 return Ok(());
             }
 
@@ -532,7 +2055,7 @@ fn main() -> Result<()> {
             if let Some(e) = enabled {
                 config.enabled = e;
                 changed = true;
-                println!("✅ Reminders {}", if e { "enabled" } else { "disabled" });
+                println!("{} Reminders {}", ui::glyph("✅"), if e { "enabled" } else { "disabled" });
             }
 
             if let Some(i) = interval {
@@ -543,62 +2066,627 @@ fn main() -> Result<()> {
                 let hours = config.interval_minutes / 60;
                 let mins = config.interval_minutes % 60;
                 if hours > 0 && mins > 0 {
-                    println!("✅ Reminder interval set to {}h {}m", hours, mins);
+                    println!("{} Reminder interval set to {}h {}m", ui::glyph("✅"), hours, mins);
                 } else if hours > 0 {
-                    println!("✅ Reminder interval set to {}h", hours);
+                    println!("{} Reminder interval set to {}h", ui::glyph("✅"), hours);
                 } else {
-                    println!("✅ Reminder interval set to {}m", mins);
+                    println!("{} Reminder interval set to {}m", ui::glyph("✅"), mins);
                 }
             }
 
             if let Some(n) = notify {
                 config.use_notify_send = n;
                 changed = true;
-                println!("✅ Desktop notifications {}", if n { "enabled" } else { "disabled" });
+                println!("{} Desktop notifications {}", ui::glyph("✅"), if n { "enabled" } else { "disabled" });
             }
 
             if let Some(w) = wall {
                 config.use_wall = w;
                 changed = true;
-                println!("✅ Terminal wall messages {}", if w { "enabled" } else { "disabled" });
+                println!("{} Terminal wall messages {}", ui::glyph("✅"), if w { "enabled" } else { "disabled" });
             }
 
             if let Some(start) = wall_quiet_start {
                 config.wall_quiet_start_hour = start.min(23);
                 changed = true;
-                println!("✅ Wall quiet start hour set to {}:00", start);
+                println!("{} Wall quiet start hour set to {}:00", ui::glyph("✅"), start);
             }
 
             if let Some(end) = wall_quiet_end {
                 config.wall_quiet_end_hour = end.min(23);
                 changed = true;
-                println!("✅ Wall quiet end hour set to {}:00", end);
+                println!("{} Wall quiet end hour set to {}:00", ui::glyph("✅"), end);
             }
 
             if let Some(s) = start_from_quiet_end {
                 config.start_from_quiet_end = s;
                 changed = true;
                 if s {
-                    println!("✅ Reminders will start from quiet-end time");
+                    println!("{} Reminders will start from quiet-end time", ui::glyph("✅"));
                 } else {
-                    println!("✅ Reminders will use fixed interval");
+                    println!("{} Reminders will use fixed interval", ui::glyph("✅"));
                 }
             }
 
+            if let Some(h) = due_soon_hours {
+                config.reminder_due_soon_hours = h;
+                changed = true;
+                println!("{} Reminders flag tasks due within {}h", ui::glyph("✅"), h);
+            }
+
+            if let Some(minutes) = skip_if_idle {
+                config.skip_if_idle_minutes = (minutes > 0).then_some(minutes);
+                changed = true;
+                match config.skip_if_idle_minutes {
+                    Some(minutes) => println!("{} Reminders skipped after {} idle minutes", ui::glyph("✅"), minutes),
+                    None => println!("{} Reminders no longer depend on idle time", ui::glyph("✅")),
+                }
+            }
+
+            if let Some(goal) = daily_goal {
+                config.daily_goal = (goal > 0).then_some(goal);
+                changed = true;
+                match config.daily_goal {
+                    Some(goal) => println!("{} Daily goal set to {} tasks", ui::glyph("✅"), goal),
+                    None => println!("{} Daily goal turned off", ui::glyph("✅")),
+                }
+            }
+
+            if let Some(minutes) = daily_capacity {
+                config.daily_capacity_minutes = minutes;
+                changed = true;
+                println!("{} Forecast assumes {} minutes of work a day", ui::glyph("✅"), minutes);
+            }
+
+            if let Some(minutes) = default_estimate {
+                config.default_estimate_minutes = minutes;
+                changed = true;
+                println!("{} Forecast assumes {} minutes for tasks without an estimate", ui::glyph("✅"), minutes);
+            }
+
+            if let Some(prefix) = tag_prefix {
+                config.tag_prefix = parse_tag_prefix(&prefix)?;
+                changed = true;
+                println!("{} Tags will be shown as {}tag", ui::glyph("✅"), config.tag_prefix);
+            }
+
+            if let Some(hint) = estimate_hint {
+                config.estimate_hint = hint;
+                changed = true;
+                if hint {
+                    println!("{} Tasks without an estimate will show a hint", ui::glyph("✅"));
+                } else {
+                    println!("{} No-estimate hint turned off", ui::glyph("✅"));
+                }
+            }
+
+            if let Some(sound) = completion_sound {
+                config.completion_sound = match sound.trim().to_lowercase().as_str() {
+                    "none" | "off" | "" => None,
+                    _ => Some(sound.trim().to_string()),
+                };
+                changed = true;
+                match &config.completion_sound {
+                    Some(sound) => println!("{} Completing a task will play: {}", ui::glyph("✅"), sound),
+                    None => println!("{} Completion sound turned off", ui::glyph("✅")),
+                }
+            }
+
+            if let Some(undated) = remind_undated {
+                config.remind_undated = undated;
+                changed = true;
+                if undated {
+                    println!("{} Reminders include tasks without a due date", ui::glyph("✅"));
+                } else {
+                    println!("{} Reminders only mention tasks with a due date", ui::glyph("✅"));
+                }
+            }
+
+            if let Some(enabled) = file_sink {
+                config.use_file_sink = enabled;
+                changed = true;
+                println!("{} File sink {}", ui::glyph("✅"), if enabled { "enabled" } else { "disabled" });
+            }
+
+            if let Some(path) = file_sink_path {
+                config.file_sink_path = match path.trim() {
+                    "" | "default" | "none" => None,
+                    path => Some(path.to_string()),
+                };
+                changed = true;
+                println!("{} File sink writes to {}", ui::glyph("✅"), reminders::file_sink_path(&config).display());
+            }
+
+            if let Some(append) = file_sink_append {
+                config.file_sink_append = append;
+                changed = true;
+                if append {
+                    println!("{} File sink will append each reminder", ui::glyph("✅"));
+                } else {
+                    println!("{} File sink will hold only the latest reminder", ui::glyph("✅"));
+                }
+            }
+
+            if let Some(grace) = overdue_grace {
+                config.overdue_grace_minutes = grace;
+                changed = true;
+                println!("{} Tasks count as overdue {} minutes after they are due", ui::glyph("✅"), grace);
+            }
+
+            if let Some(lowercase) = lowercase_projects {
+                config.lowercase_projects = lowercase;
+                changed = true;
+                if lowercase {
+                    println!("{} New project names will be stored in lowercase", ui::glyph("✅"));
+                } else {
+                    println!("{} Project names keep their case", ui::glyph("✅"));
+                }
+            }
+
+            if let Some(lowercase) = lowercase_tags {
+                config.lowercase_tags = lowercase;
+                changed = true;
+                if lowercase {
+                    println!("{} New tags will be stored in lowercase", ui::glyph("✅"));
+                } else {
+                    println!("{} Tags keep their case", ui::glyph("✅"));
+                }
+            }
+
+            if let Some(strict) = strict_projects {
+                config.strict_projects = strict;
+                changed = true;
+                if strict {
+                    println!("{} Project names differing only by case will be rejected", ui::glyph("✅"));
+                } else {
+                    println!("{} Any project name is accepted", ui::glyph("✅"));
+                }
+            }
+
+            if let Some(w) = week_start {
+                config.week_start = parse_week_start(&w)?;
+                changed = true;
+                println!("{} Week starts on {}", ui::glyph("✅"), config.week_start.as_str());
+            }
+
+            if let Some(labels) = priority_labels {
+                config.priority_labels = parse_priority_labels(&labels)?;
+                changed = true;
+                match &config.priority_labels {
+                    Some(labels) => println!("{} Priority labels set to {}", ui::glyph("✅"), labels.join(", ")),
+                    None => println!("{} Priority labels reset to defaults", ui::glyph("✅")),
+                }
+            }
+
+            if changed && config.use_wall && config.interval_minutes < MIN_WALL_INTERVAL_MINUTES {
+                println!();
+                println!(
+                    "{} Wall messages every {}m will interrupt every terminal constantly.",
+                    ui::glyph("⚠️").yellow(),
+                    config.interval_minutes
+                );
+                println!(
+                    "   Consider an interval of at least {}m, or desktop notifications (--wall false --notify true).",
+                    MIN_WALL_INTERVAL_MINUTES
+                );
+            }
+
             if changed {
                 db.save_config(&config)?;
                 println!();
-                println!("⚠️  To apply changes, run the following commands:");
+                println!("{}  To apply changes, run the following commands:", ui::glyph("⚠️"));
                 println!("   1. systemctl --user daemon-reload");
                 println!("   2. systemctl --user restart todo-queue.timer");
             }
         }
 
-        Commands::Stats => {
-            let tasks = db.list_tasks(true)?;
-            ui::print_stats(&tasks);
+        Commands::Query { sql, allow_sql } => {
+            if !allow_sql {
+                return warn(json, "Raw SQL is disabled. Re-run with --allow-sql to run a read-only SELECT.");
+            }
+            let result = match db.select(&sql) {
+                Ok(result) => result,
+                Err(err) => return warn(json, format!("Query failed: {:#}", err)),
+            };
+
+            if json {
+                let rows: Vec<serde_json::Map<String, serde_json::Value>> = result
+                    .rows
+                    .into_iter()
+                    .map(|row| result.columns.iter().cloned().zip(row).collect())
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            } else {
+                ui::print_query_result(&result);
+            }
+        }
+
+        Commands::Stats { since, project } => {
+            let mut scope = Vec::new();
+            let mut tasks = db.query_tasks(&TaskFilter {
+                include_completed: true,
+                project: project.clone(),
+                ..TaskFilter::default()
+            })?;
+
+            if let Some(ref window) = since {
+                let Some(offset) = parse_relative_duration(window) else {
+                    return warn(json, format!("Invalid window '{}'. Use e.g. '7d', '4w' or '12h'.", window));
+                };
+                let cutoff = Utc::now() - offset;
+                tasks.retain(|t| t.created_at >= cutoff || t.completed_at.is_some_and(|at| at >= cutoff));
+                scope.push(format!("created or completed in the last {}", window));
+            }
+            if let Some(ref project) = project {
+                scope.push(format!("project = {}", project));
+            }
+
+            let scope = (!scope.is_empty()).then(|| scope.join(", "));
+            ui::print_stats(&tasks, &config, scope.as_deref());
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn finds_task_by_stable_id() {
+        let mut first = Task::sample("Write report");
+        first.id = 7;
+        let mut second = Task::sample("Call bank");
+        second.id = 3;
+        let tasks = [first, second];
+
+        assert_eq!(find_task_by_index_or_title(&tasks, "id:3"), Some((1, 3)));
+        assert_eq!(find_task_by_index_or_title(&tasks, "id:2"), None);
+        assert_eq!(find_task_by_index_or_title(&tasks, "2"), Some((1, 3)));
+        assert_eq!(find_task_by_index_or_title(&tasks, "write report"), Some((0, 7)));
+    }
+
+    #[test]
+    fn accepts_distinct_priority_labels() {
+        let labels = parse_priority_labels("P3, P2, P1, P0").unwrap().unwrap();
+        assert_eq!(labels, ["P3", "P2", "P1", "P0"].map(String::from));
+        // A level may keep its own name
+        assert!(parse_priority_labels("low,normal,high,urgent").unwrap().is_some());
+        assert!(parse_priority_labels("default").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_duplicate_priority_labels() {
+        assert!(parse_priority_labels("p,p,p,p").is_err());
+        assert!(parse_priority_labels("P3,p3,P1,P0").is_err());
+    }
+
+    #[test]
+    fn rejects_labels_that_shadow_builtin_names() {
+        let err = parse_priority_labels("high,b,c,d").unwrap_err();
+        assert!(err.to_string().contains("high level"), "{}", err);
+        assert!(parse_priority_labels("a,b,c,LOW").is_err());
+    }
+
+    #[test]
+    fn edited_config_rejects_invalid_priority_labels() {
+        let text = r#"priority_labels = ["p", "p", "q", "r"]"#;
+        let err = parse_edited_config(text).unwrap_err();
+        assert!(err.contains("more than once"), "{}", err);
+    }
+
+    #[test]
+    fn parses_intervals_up_to_a_week() {
+        assert_eq!(parse_interval("30m").unwrap(), 30);
+        assert_eq!(parse_interval("2h").unwrap(), 120);
+        assert_eq!(parse_interval("45").unwrap(), 45);
+        assert_eq!(parse_interval("168h").unwrap(), MAX_INTERVAL_MINUTES);
+        assert_eq!(parse_interval("10080m").unwrap(), MAX_INTERVAL_MINUTES);
+    }
+
+    #[test]
+    fn rejects_out_of_range_intervals() {
+        for input in [
+            "169h",
+            "10081m",
+            "0m",
+            "0h",
+            "0",
+            "4294967296m",
+            "4294967295h",
+            "99999999999d",
+            "-5m",
+            "-1h",
+            "",
+            "h",
+            "2d",
+        ] {
+            assert!(parse_interval(input).is_err(), "{:?} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn parses_relative_durations_up_to_100_years() {
+        assert_eq!(parse_relative_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_relative_duration("1w"), Some(Duration::weeks(1)));
+        assert_eq!(parse_relative_duration("0m"), Some(Duration::zero()));
+        assert_eq!(parse_relative_duration("36500d"), Some(Duration::days(36_500)));
+        assert_eq!(parse_relative_duration("876000h"), Some(Duration::hours(876_000)));
+    }
+
+    #[test]
+    fn rejects_huge_or_malformed_relative_durations() {
+        for input in [
+            "36501d",
+            "5215w",
+            "876025h",
+            "99999999999d",
+            "9223372036854775807m",
+            "9223372036854775808m",
+            "4294967296m",
+            "-1d",
+            "-36500d",
+            "",
+            "d",
+            "2x",
+            "1.5h",
+        ] {
+            assert_eq!(parse_relative_duration(input), None, "{:?} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn huge_due_offsets_are_errors_not_panics() {
+        assert!(parse_due_time("99999999999d").is_err());
+        assert!(parse_completed_at("99999999999w").is_err());
+    }
+
+    #[test]
+    fn config_set_reads_booleans_by_key() {
+        let mut config = ReminderConfig::default();
+        assert_eq!(set_config_value(&mut config, "use-wall", "off").unwrap(), "use_wall = false");
+        assert_eq!(set_config_value(&mut config, "use_wall", "yes").unwrap(), "use_wall = true");
+        assert!(config.use_wall);
+
+        let err = set_config_value(&mut config, "use_wall", "maybe").unwrap_err().to_string();
+        assert!(err.contains("use_wall") && err.contains("true, false, on, off, yes, no"), "{}", err);
+    }
+
+    #[test]
+    fn config_set_takes_text_verbatim() {
+        let mut config = ReminderConfig::default();
+        set_config_value(&mut config, "completion_sound", "true").unwrap();
+        assert_eq!(config.completion_sound.as_deref(), Some("true"));
+        set_config_value(&mut config, "file_sink_path", "123").unwrap();
+        assert_eq!(config.file_sink_path.as_deref(), Some("123"));
+        set_config_value(&mut config, "completion_sound", "none").unwrap();
+        assert_eq!(config.completion_sound, None);
+    }
+
+    #[test]
+    fn config_set_checks_number_ranges() {
+        let mut config = ReminderConfig::default();
+        set_config_value(&mut config, "wall_quiet_start_hour", "23").unwrap();
+        assert_eq!(config.wall_quiet_start_hour, 23);
+        set_config_value(&mut config, "daily_goal", "off").unwrap();
+        assert_eq!(config.daily_goal, None);
+
+        for (key, value) in [
+            ("wall_quiet_start_hour", "24"),
+            ("daily_capacity_minutes", "0"),
+            ("overdue_grace_minutes", "-1"),
+            ("overdue_grace_minutes", "4294967296"),
+            ("daily_goal", "true"),
+        ] {
+            let err = set_config_value(&mut config, key, value).unwrap_err().to_string();
+            assert!(err.contains(key) && err.contains("whole number"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn config_set_handles_every_key() {
+        let mut config = ReminderConfig::default();
+        let defaults = serde_json::to_value(ReminderConfig::default()).unwrap();
+        for key in defaults.as_object().unwrap().keys() {
+            let err = set_config_value(&mut config, key, "").map(|_| String::new()).unwrap_or_else(|e| e.to_string());
+            assert!(!err.contains("edit-config"), "{} has no parser", key);
+        }
+        assert!(set_config_value(&mut config, "no_such_key", "1").unwrap_err().to_string().contains("Unknown"));
+    }
+
+    #[test]
+    fn usage_errors_as_json() {
+        let err = Cli::try_parse_from(["todo", "--json", "list", "--bogus"]).err().unwrap();
+        let value = clap_error_json(&err);
+        assert_eq!(value["code"], EXIT_COMMAND_FAILED);
+        let message = value["error"].as_str().unwrap();
+        assert!(message.contains("--bogus") && !message.starts_with("error:"), "{}", message);
+    }
+
+    #[test]
+    fn json_flag_found_before_separator_only() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>().into_iter();
+        assert!(json_requested(args(&["todo", "add", "--json", "x"])));
+        assert!(!json_requested(args(&["todo", "add", "--", "--json"])));
+        assert!(!json_requested(args(&["todo", "list"])));
+    }
+
+    #[test]
+    fn edited_config_round_trips_as_toml() {
+        let config = ReminderConfig {
+            daily_goal: Some(5),
+            priority_labels: Some(["P3", "P2", "P1", "P0"].map(String::from)),
+            ..ReminderConfig::default()
+        };
+        let text = config_to_toml(&config).unwrap();
+        assert!(text.contains("# completion_sound = ..."), "{}", text);
+
+        let parsed = parse_edited_config(&text).unwrap();
+        assert!(config_changes(&config, &parsed).is_empty());
+    }
+
+    #[test]
+    fn edited_config_reports_toml_errors() {
+        let err = parse_edited_config("interval_minutes = \"often\"").unwrap_err();
+        assert!(err.starts_with("Invalid value"), "{}", err);
+        let err = parse_edited_config("interval_minutes = ").unwrap_err();
+        assert!(err.starts_with("Invalid TOML"), "{}", err);
+        let err = parse_edited_config("colour = true").unwrap_err();
+        assert!(err.contains("'colour'"), "{}", err);
+        // Left-out keys keep their defaults
+        assert_eq!(parse_edited_config("use_wall = true").unwrap().interval_minutes, 180);
+    }
+
+    #[test]
+    fn edit_errors_are_replaced_not_stacked() {
+        let draft = "use_wall = maybe\n";
+        let once = annotate_edit_error(draft, "Invalid TOML: bad\nvalue");
+        assert_eq!(once, "# ERROR: Invalid TOML: bad\n# ERROR: value\nuse_wall = maybe\n");
+        let twice = annotate_edit_error(&once, "Invalid TOML: still bad");
+        assert_eq!(twice, "# ERROR: Invalid TOML: still bad\nuse_wall = maybe\n");
+        assert_eq!(strip_edit_error(&twice), draft);
+    }
+
+    #[test]
+    fn parses_12h_times() {
+        let time = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(parse_12h_time("9pm"), time(21, 0));
+        assert_eq!(parse_12h_time("9:30 AM"), time(9, 30));
+        assert_eq!(parse_12h_time("12am"), time(0, 0));
+        assert_eq!(parse_12h_time("12:15 pm"), time(12, 15));
+        assert_eq!(parse_12h_time("21:00"), None);
+        assert_eq!(parse_12h_time("13pm"), None);
+    }
+
+    #[test]
+    fn am_pm_due_times_are_utc() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(parse_12h_due("9pm", today), Some(Utc.with_ymd_and_hms(2024, 1, 1, 21, 0, 0).unwrap()));
+        assert_eq!(parse_12h_due("9:30 AM", today), Some(Utc.with_ymd_and_hms(2024, 1, 1, 9, 30, 0).unwrap()));
+        assert_eq!(
+            parse_12h_due("2024-01-05 9:00 PM", today),
+            Some(Utc.with_ymd_and_hms(2024, 1, 5, 21, 0, 0).unwrap())
+        );
+        assert_eq!(parse_12h_due("2024-01-05", today), None);
+    }
+
+    #[test]
+    fn am_pm_and_24_hour_due_times_agree() {
+        assert_eq!(parse_due_time("2024-01-05 9:00 PM").unwrap(), parse_due_time("2024-01-05 21:00").unwrap());
+        // Retry once in case the day rolled over between the two parses
+        let same_day = || parse_due_time("9pm").unwrap() == parse_due_time("21:00").unwrap();
+        assert!(same_day() || same_day());
+    }
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, n).unwrap()
+    }
+
+    #[test]
+    fn forecast_carries_big_tasks_over_days() {
+        // 90 + 200 = 290: the second task spills 50 minutes into day two,
+        // the third takes the other 190 of day two and 110 of day three
+        let finishes = forecast_finish_dates(&[90, 200, 300], day(1), 240);
+        assert_eq!(finishes, vec![day(1), day(2), day(3)]);
+        // A task longer than several days spans all of them
+        assert_eq!(forecast_finish_dates(&[600], day(1), 240), vec![day(3)]);
+    }
+
+    #[test]
+    fn forecast_exactly_full_day_stays_on_that_day() {
+        let finishes = forecast_finish_dates(&[120, 120, 1], day(1), 240);
+        assert_eq!(finishes, vec![day(1), day(1), day(2)]);
+        assert_eq!(forecast_finish_dates(&[240, 240], day(1), 240), vec![day(1), day(2)]);
+    }
+
+    #[test]
+    fn forecast_zero_estimates_take_no_time() {
+        assert_eq!(forecast_finish_dates(&[0, 0], day(1), 240), vec![day(1), day(1)]);
+        assert_eq!(forecast_finish_dates(&[240, 0], day(1), 240), vec![day(1), day(1)]);
+        assert!(forecast_finish_dates(&[], day(1), 240).is_empty());
+    }
+
+    #[test]
+    fn defers_stack_on_the_due_date() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let due = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        let mut task = Task::sample("Deferred");
+        task.due_at = Some(due);
+
+        defer_task(&mut task, Duration::days(1), now);
+        defer_task(&mut task, Duration::days(1), now);
+        assert_eq!(task.due_at, Some(due + Duration::days(2)));
+    }
+
+    #[test]
+    fn defer_without_due_date_starts_from_now() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut task = Task::sample("Undated");
+        defer_task(&mut task, Duration::hours(2), now);
+        assert_eq!(task.due_at, Some(now + Duration::hours(2)));
+    }
+
+    #[test]
+    fn defer_by_hours_drops_all_day() {
+        let now = Utc::now();
+        let mut task = Task::sample("All day");
+        task.all_day = true;
+        defer_task(&mut task, Duration::days(2), now);
+        assert!(task.all_day);
+        defer_task(&mut task, Duration::hours(3), now);
+        assert!(!task.all_day);
+    }
+
+    #[test]
+    fn snooze_leaves_the_due_date_alone() {
+        let db = Database::in_memory().unwrap();
+        let due = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+        let mut task = Task::sample("Snoozed");
+        task.due_at = Some(due);
+        let id = db.add_task(&task).unwrap();
+
+        let until = Utc::now() + Duration::hours(4);
+        assert!(db.snooze_task(id, Some(until)).unwrap());
+        let stored = db.get_task(id).unwrap().unwrap();
+        assert_eq!(stored.due_at, Some(due));
+        assert!(stored.is_snoozed());
+
+        db.snooze_task(id, None).unwrap();
+        let stored = db.get_task(id).unwrap().unwrap();
+        assert_eq!(stored.due_at, Some(due));
+        assert!(!stored.is_snoozed());
+    }
+
+    #[test]
+    fn clean_title_rejects_blank_titles() {
+        assert_eq!(clean_title(""), None);
+        assert_eq!(clean_title("   "), None);
+        assert_eq!(clean_title("\t\n "), None);
+    }
+
+    #[test]
+    fn clean_title_trims_surrounding_whitespace() {
+        assert_eq!(clean_title("  buy milk  ").as_deref(), Some("buy milk"));
+        assert_eq!(clean_title("buy  milk").as_deref(), Some("buy  milk"));
+    }
+
+    #[test]
+    fn padded_title_is_a_duplicate_of_the_trimmed_one() {
+        let db = Database::in_memory().unwrap();
+        db.add_task(&Task::sample("buy milk")).unwrap();
+
+        let title = clean_title(" buy milk ").unwrap();
+        let tasks = db.list_tasks(false).unwrap();
+        assert!(tasks.iter().any(|t| t.title.eq_ignore_ascii_case(&title)));
+        let err = db.add_task(&Task::sample(&title)).unwrap_err();
+        assert!(err.downcast_ref::<DuplicateTitle>().is_some());
+    }
+}
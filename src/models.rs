@@ -1,7 +1,8 @@
-use chrono::{DateTime, Timelike, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Declared lowest to highest, so the derived ordering ranks by urgency
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Priority {
     Low,
     Medium,
@@ -19,7 +20,24 @@ impl Priority {
         }
     }
 
-    #[allow(dead_code)]
+    /// Built-in textual name, used when no custom label is configured
+    pub fn name(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+            Priority::Critical => "Critical",
+        }
+    }
+
+    /// Display label, taken from `priority_labels` in the config if set
+    pub fn label(&self, config: &ReminderConfig) -> String {
+        match &config.priority_labels {
+            Some(labels) => labels[self.weight() as usize - 1].clone(),
+            None => self.name().to_string(),
+        }
+    }
+
     pub fn weight(&self) -> u8 {
         match self {
             Priority::Low => 1,
@@ -28,6 +46,16 @@ impl Priority {
             Priority::Critical => 4,
         }
     }
+
+    /// Inverse of `weight`, clamping out-of-range values to Low/Critical
+    pub fn from_weight(weight: i32) -> Priority {
+        match weight {
+            i32::MIN..=1 => Priority::Low,
+            2 => Priority::Medium,
+            3 => Priority::High,
+            _ => Priority::Critical,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,23 +70,122 @@ pub struct Task {
     pub tags: Vec<String>,
     pub project: Option<String>,
     pub estimated_minutes: Option<u32>,
+    /// Reminders skip this task until this time; `due_at` is unaffected
+    pub snoozed_until: Option<DateTime<Utc>>,
+    pub checklist: Vec<ChecklistItem>,
+    /// Free-form marker (e.g. an emoji) shown before the title
+    pub label: Option<String>,
+    /// The due date is a whole day rather than a specific time
+    #[serde(default)]
+    pub all_day: bool,
+    /// Reminders skip this task entirely when false
+    #[serde(default = "default_true")]
+    pub reminders_enabled: bool,
+    /// Waiting on something else: kept out of `next`, `pick` and reminders
+    #[serde(default)]
+    pub blocked: bool,
+    /// Related files (absolute paths) and URLs
+    #[serde(default)]
+    pub attachments: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A lightweight step inside a task, tracked without being a task itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
 }
 
 impl Task {
-    pub fn is_overdue(&self) -> bool {
-        if let Some(due) = self.due_at {
-            due < Utc::now() && self.completed_at.is_none()
-        } else {
-            false
+    /// Pending and past due by more than the configured grace period
+    pub fn is_overdue(&self, config: &ReminderConfig) -> bool {
+        self.is_overdue_at(Utc::now(), config.overdue_grace_minutes)
+    }
+
+    /// `is_overdue` at a given moment, for checking the grace boundary
+    pub fn is_overdue_at(&self, now: DateTime<Utc>, grace_minutes: u32) -> bool {
+        match self.due_at {
+            Some(due) => self.completed_at.is_none() && due + Duration::minutes(grace_minutes as i64) < now,
+            None => false,
         }
     }
 
     pub fn is_completed(&self) -> bool {
         self.completed_at.is_some()
     }
+
+    pub fn is_snoozed(&self) -> bool {
+        self.snoozed_until.is_some_and(|until| until > Utc::now())
+    }
+
+    /// Whether the task can be worked on now, as used by `next`, `pick`,
+    /// `top` and `list --actionable`: it is not completed and not blocked.
+    /// Snoozing and `--no-remind` only silence reminders, so snoozed and
+    /// opted-out tasks are still actionable.
+    pub fn is_actionable(&self) -> bool {
+        !self.is_completed() && !self.blocked
+    }
+}
+
+#[cfg(test)]
+impl Task {
+    /// A pending medium-priority task with nothing else set
+    pub fn sample(title: &str) -> Task {
+        Task {
+            id: 0,
+            title: title.to_string(),
+            description: None,
+            priority: Priority::Medium,
+            created_at: Utc::now(),
+            due_at: None,
+            completed_at: None,
+            tags: Vec::new(),
+            project: None,
+            estimated_minutes: None,
+            snoozed_until: None,
+            checklist: Vec::new(),
+            label: None,
+            all_day: false,
+            reminders_enabled: true,
+            blocked: false,
+            attachments: Vec::new(),
+        }
+    }
 }
 
+/// First day of the week for weekly date windows
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum WeekStart {
+    Sunday,
+    #[default]
+    Monday,
+}
+
+impl WeekStart {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WeekStart::Sunday => "Sunday",
+            WeekStart::Monday => "Monday",
+        }
+    }
+
+    /// First day of the week containing `day`
+    pub fn week_start_date(&self, day: NaiveDate) -> NaiveDate {
+        let offset = match self {
+            WeekStart::Sunday => day.weekday().num_days_from_sunday(),
+            WeekStart::Monday => day.weekday().num_days_from_monday(),
+        };
+        day - Duration::days(offset as i64)
+    }
+}
+
+/// Fields missing from a stored config fall back to their defaults
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReminderConfig {
     pub enabled: bool,
     pub interval_minutes: u32,
@@ -67,6 +194,41 @@ pub struct ReminderConfig {
     pub wall_quiet_start_hour: u32,  // Start hour for wall quiet period (0-23)
     pub wall_quiet_end_hour: u32,    // End hour for wall quiet period (0-23)
     pub start_from_quiet_end: bool,  // Start reminders from quiet-end time
+    pub week_start: WeekStart,
+    /// Custom priority labels, ordered Low, Medium, High, Critical
+    pub priority_labels: Option<[String; 4]>,
+    /// Tasks due within this many hours are flagged as due soon in reminders
+    pub reminder_due_soon_hours: u32,
+    /// Number of tasks to complete per day, shown as progress in stats
+    pub daily_goal: Option<u32>,
+    /// Minutes of task work per day assumed by `todo forecast`
+    pub daily_capacity_minutes: u32,
+    /// Estimate `todo forecast` assumes for tasks without one
+    pub default_estimate_minutes: u32,
+    /// Character shown before each tag, e.g. "#" or "@"
+    pub tag_prefix: String,
+    /// Show a "no estimate" hint on pending tasks without an estimate
+    pub estimate_hint: bool,
+    /// Played by `todo done`: "bell" for the terminal bell, or a shell command
+    pub completion_sound: Option<String>,
+    /// Include tasks without a due date in reminders
+    pub remind_undated: bool,
+    /// Skip reminders while the user has been idle at least this many minutes
+    pub skip_if_idle_minutes: Option<u32>,
+    /// Store project names in lowercase
+    pub lowercase_projects: bool,
+    /// Store tags in lowercase
+    pub lowercase_tags: bool,
+    /// Reject project names that differ from an existing one only by case
+    pub strict_projects: bool,
+    /// Minutes past the due time before a task counts as overdue
+    pub overdue_grace_minutes: u32,
+    /// Also write each reminder to a file for status bars and other pollers
+    pub use_file_sink: bool,
+    /// Where the file sink writes; `~/.todo-queue/reminder.txt` when unset
+    pub file_sink_path: Option<String>,
+    /// Append reminders to the file sink instead of replacing its contents
+    pub file_sink_append: bool,
 }
 
 impl Default for ReminderConfig {
@@ -79,6 +241,24 @@ impl Default for ReminderConfig {
             wall_quiet_start_hour: 18,  // 6 PM
             wall_quiet_end_hour: 9,     // 9 AM
             start_from_quiet_end: false,
+            week_start: WeekStart::Monday,
+            priority_labels: None,
+            reminder_due_soon_hours: 24,
+            daily_goal: None,
+            tag_prefix: "#".to_string(),
+            estimate_hint: false,
+            completion_sound: None,
+            remind_undated: true,
+            skip_if_idle_minutes: None,
+            daily_capacity_minutes: 240,
+            default_estimate_minutes: 30,
+            lowercase_projects: false,
+            lowercase_tags: false,
+            strict_projects: false,
+            overdue_grace_minutes: 0,
+            use_file_sink: false,
+            file_sink_path: None,
+            file_sink_append: false,
         }
     }
 }
@@ -87,7 +267,7 @@ impl ReminderConfig {
     /// Check if current time is within wall quiet hours
     pub fn is_wall_quiet_hours(&self) -> bool {
         let now = Utc::now();
-        let hour = now.hour() as u32;
+        let hour = now.hour();
         
         // 处理跨天情况（例如：18:00 - 09:00）
         if self.wall_quiet_start_hour > self.wall_quiet_end_hour {
@@ -99,3 +279,65 @@ impl ReminderConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priorities_order_by_urgency() {
+        assert!(Priority::Critical > Priority::Medium);
+        assert!(Priority::High > Priority::Medium);
+        assert!(Priority::Medium > Priority::Low);
+        assert_eq!(
+            [Priority::High, Priority::Low, Priority::Critical, Priority::Medium].iter().max(),
+            Some(&Priority::Critical)
+        );
+    }
+
+    #[test]
+    fn overdue_only_after_grace_window() {
+        let due = Utc::now();
+        let mut task = Task::sample("Due");
+        task.due_at = Some(due);
+
+        assert!(!task.is_overdue_at(due + Duration::minutes(15), 15));
+        assert!(!task.is_overdue_at(due + Duration::minutes(15) - Duration::seconds(1), 15));
+        assert!(task.is_overdue_at(due + Duration::minutes(15) + Duration::seconds(1), 15));
+        assert!(task.is_overdue_at(due + Duration::seconds(1), 0));
+    }
+
+    #[test]
+    fn completed_and_blocked_tasks_are_not_actionable() {
+        assert!(Task::sample("Open").is_actionable());
+
+        let mut completed = Task::sample("Completed");
+        completed.completed_at = Some(Utc::now());
+        assert!(!completed.is_actionable());
+
+        let mut blocked = Task::sample("Blocked");
+        blocked.blocked = true;
+        assert!(!blocked.is_actionable());
+    }
+
+    #[test]
+    fn snoozed_and_no_remind_tasks_stay_actionable() {
+        let mut snoozed = Task::sample("Snoozed");
+        snoozed.snoozed_until = Some(Utc::now() + Duration::hours(1));
+        assert!(snoozed.is_snoozed());
+        assert!(snoozed.is_actionable());
+
+        let mut quiet = Task::sample("Quiet");
+        quiet.reminders_enabled = false;
+        assert!(quiet.is_actionable());
+    }
+
+    #[test]
+    fn completed_task_is_never_overdue() {
+        let due = Utc::now();
+        let mut task = Task::sample("Done");
+        task.due_at = Some(due);
+        task.completed_at = Some(due);
+        assert!(!task.is_overdue_at(due + Duration::days(1), 0));
+    }
+}
@@ -1,54 +1,264 @@
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use tracing::{debug, info, instrument, warn};
 
-use crate::models::ReminderConfig;
+use crate::models::{ReminderConfig, Task};
 
+#[instrument(skip_all, fields(lines = message.lines().count()))]
 pub fn send_reminder(message: &str, config: &ReminderConfig) -> Result<()> {
     if !config.enabled {
+        debug!("reminders disabled, not sending");
         return Ok(());
     }
 
     // Always print to stdout for debugging
-    println!("📢 {}", message);
+    println!("{} {}", crate::ui::glyph("📢"), message);
 
     // Try notify-send first (desktop notification) - no quiet hours
     if config.use_notify_send {
-        let _ = Command::new("notify-send")
-            .args(["-i", "appointment", "Todo Reminder", message])
-            .status();
+        notify_desktop(message);
     }
 
     // Try wall (terminal broadcast) - respect quiet hours
     if config.use_wall {
         // Check if within quiet hours
         if config.is_wall_quiet_hours() {
-            println!("🔇 Wall message suppressed (quiet hours: {}:00 - {}:00)", 
+            info!("wall suppressed by quiet hours");
+            println!("{} Wall message suppressed (quiet hours: {}:00 - {}:00)", crate::ui::glyph("🔇"), 
                      config.wall_quiet_start_hour, config.wall_quiet_end_hour);
         } else {
-            let _ = Command::new("wall").arg(message).status();
+            broadcast_wall(message);
         }
     }
 
+    if config.use_file_sink {
+        write_file_sink(message, config);
+    }
+
     Ok(())
 }
 
+/// Message sent by `todo remind --test`
+const TEST_MESSAGE: &str = "Test reminder from todo-queue";
+
+/// Send a fixed test message through every enabled backend right away,
+/// ignoring quiet hours, and report whether each backend succeeded
+pub fn send_test_reminder(config: &ReminderConfig) -> Vec<(&'static str, bool)> {
+    let mut results = vec![("stdout", true)];
+    println!("{} {}", crate::ui::glyph("📢"), TEST_MESSAGE);
+
+    if config.use_notify_send {
+        results.push(("notify-send", notify_desktop(TEST_MESSAGE)));
+    }
+    if config.use_wall {
+        results.push(("wall", broadcast_wall(TEST_MESSAGE)));
+    }
+    if config.use_file_sink {
+        results.push(("file", write_file_sink(TEST_MESSAGE, config)));
+    }
+
+    results
+}
+
+/// Path the file sink writes to
+pub fn file_sink_path(config: &ReminderConfig) -> PathBuf {
+    match &config.file_sink_path {
+        Some(path) => PathBuf::from(path),
+        None => dirs::home_dir()
+            .expect("Cannot determine home directory")
+            .join(".todo-queue")
+            .join("reminder.txt"),
+    }
+}
+
+/// Replace (or append to) the file sink. The new contents go to a temporary
+/// file that is renamed over the sink, so pollers never see a partial write.
+fn write_file_sink(message: &str, config: &ReminderConfig) -> bool {
+    let path = file_sink_path(config);
+    let result = (|| -> std::io::Result<()> {
+        let mut contents = String::new();
+        if config.file_sink_append {
+            contents = std::fs::read_to_string(&path).unwrap_or_default();
+            contents.push_str(&format!("[{}]\n", chrono::Local::now().format("%Y-%m-%d %H:%M")));
+        }
+        contents.push_str(message);
+        if !message.is_empty() {
+            contents.push('\n');
+        }
+
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp = path.with_file_name(temp_name);
+        let mut file = File::create(&temp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&temp, &path)
+    })();
+
+    match result {
+        Ok(()) => {
+            debug!(path = %path.display(), "reminder written to file sink");
+            true
+        }
+        Err(err) => {
+            warn!(path = %path.display(), %err, "could not write file sink");
+            false
+        }
+    }
+}
+
+fn notify_desktop(message: &str) -> bool {
+    let status = Command::new("notify-send")
+        .args(["-i", "appointment", "Todo Reminder", message])
+        .status();
+    log_backend_status("notify-send", &status)
+}
+
+fn broadcast_wall(message: &str) -> bool {
+    let status = Command::new("wall").arg(message).status();
+    log_backend_status("wall", &status)
+}
+
+/// Log how a backend command finished and return whether it succeeded
+fn log_backend_status(backend: &str, status: &std::io::Result<std::process::ExitStatus>) -> bool {
+    match status {
+        Ok(status) if status.success() => {
+            debug!(backend, "reminder delivered");
+            true
+        }
+        Ok(status) => {
+            warn!(backend, %status, "reminder backend failed");
+            false
+        }
+        Err(err) => {
+            warn!(backend, %err, "reminder backend could not be started");
+            false
+        }
+    }
+}
+
+/// Minutes since the user's last input, from `xprintidle` (X11) or else
+/// logind's idle hint. `None` when neither is available.
+fn idle_minutes() -> Option<u64> {
+    let xprintidle = Command::new("xprintidle")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8_lossy(&out.stdout).trim().parse::<u64>().ok());
+    if let Some(millis) = xprintidle {
+        return Some(millis / 60_000);
+    }
+
+    let Some(user) = current_user() else {
+        debug!("no idle detection available");
+        return None;
+    };
+    let out = Command::new("loginctl")
+        .args(["show-user", &user, "--property=IdleHint", "--property=IdleSinceHint"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success());
+    let Some(out) = out else {
+        debug!("no idle detection available");
+        return None;
+    };
+    parse_logind_idle(&String::from_utf8_lossy(&out.stdout), Utc::now())
+}
+
+/// The user `loginctl` should ask about: our uid, or `$USER` without /proc
+fn current_user() -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let Ok(meta) = std::fs::metadata("/proc/self") {
+            return Some(meta.uid().to_string());
+        }
+    }
+    std::env::var("USER").ok().filter(|user| !user.is_empty())
+}
+
+/// Read idle minutes from `loginctl show-user` properties: 0 when not idle,
+/// otherwise the time since `IdleSinceHint` (microseconds since the epoch)
+fn parse_logind_idle(output: &str, now: DateTime<Utc>) -> Option<u64> {
+    let property = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+            .map(str::trim)
+    };
+    if property("IdleHint")? != "yes" {
+        return Some(0);
+    }
+    let since_micros: i64 = property("IdleSinceHint")?.parse().ok()?;
+    let since = DateTime::from_timestamp_micros(since_micros).filter(|_| since_micros > 0)?;
+    Some((now - since).num_minutes().max(0) as u64)
+}
+
+#[instrument(skip_all)]
 pub fn check_reminders(config: &ReminderConfig) -> Result<()> {
     if !config.enabled {
+        debug!("reminders disabled, skipping check");
         return Ok(());
     }
 
-    let db_path = dirs::home_dir()
+    // Nobody is at the machine; the next run after they return reminds them
+    if let Some(threshold) = config.skip_if_idle_minutes {
+        if let Some(idle) = idle_minutes() {
+            if idle >= u64::from(threshold) {
+                info!(idle, threshold, "user is idle, skipping reminder");
+                return Ok(());
+            }
+        }
+    }
+
+    let data_dir = dirs::home_dir()
         .expect("Cannot determine home directory")
-        .join(".todo-queue")
-        .join("tasks.db");
+        .join(".todo-queue");
+
+    std::fs::create_dir_all(&data_dir)?;
+
+    // Held until this function returns; an overlapping run skips silently
+    let Some(_lock) = try_lock_reminders(&data_dir.join("remind.lock"))? else {
+        info!("another reminder run holds the lock, skipping");
+        return Ok(());
+    };
 
-    let db = crate::database::Database::new(db_path)?;
+    let db = crate::database::Database::new(data_dir.join("tasks.db"))?;
 
-    // Get all pending tasks
     let tasks = db.list_tasks(false)?;
-    
-    if tasks.is_empty() {
+    debug!(pending = tasks.len(), "collected tasks for reminder");
+
+    // Nothing left to do (e.g. everything was just completed): stay silent,
+    // but don't leave a stale reminder in an overwritten file sink
+    let Some(message) = build_reminder_message(&tasks, config) else {
+        if config.use_file_sink && !config.file_sink_append {
+            write_file_sink("", config);
+        }
         return Ok(());
+    };
+    send_reminder(&message, config)?;
+
+    Ok(())
+}
+
+/// Build the reminder text for the tasks that still need attention, or
+/// `None` when every task is completed, snoozed, blocked or opted out of
+/// reminders (including undated tasks when `remind_undated` is off)
+pub fn build_reminder_message(tasks: &[Task], config: &ReminderConfig) -> Option<String> {
+    let tasks: Vec<&Task> = tasks
+        .iter()
+        // Reminders narrow the actionable tasks further: snoozed and opted-out
+        // tasks stay actionable but are not nagged about
+        .filter(|t| t.is_actionable() && !t.is_snoozed() && t.reminders_enabled)
+        .filter(|t| config.remind_undated || t.due_at.is_some())
+        .collect();
+
+    if tasks.is_empty() {
+        return None;
     }
 
     // 构建提醒消息，包含所有任务
@@ -62,27 +272,185 @@ pub fn check_reminders(config: &ReminderConfig) -> Result<()> {
     }
     
     // 添加每个任务的信息
+    let now = Utc::now();
     for (idx, task) in tasks.iter().enumerate() {
-        let task_info = if task.is_overdue() {
-            format!("⚠️ [{}] {} (OVERDUE)", idx + 1, task.title)
-        } else if let Some(due) = task.due_at {
-            let now = chrono::Utc::now();
-            let duration = due.signed_duration_since(now);
-            if duration.num_hours() < 24 && duration.num_hours() > 0 {
-                format!("  [{}] {} (due in {}h)", idx + 1, task.title, duration.num_hours())
-            } else if duration.num_hours() <= 0 {
-                format!("⚠️ [{}] {} (OVERDUE)", idx + 1, task.title)
-            } else {
-                format!("  [{}] {}", idx + 1, task.title)
+        let task_info = match due_hint(task, now, config) {
+            Some(DueHint::Overdue) => {
+                format!("{} [{}] {} (OVERDUE)", crate::ui::glyph("⚠️"), idx + 1, task.title)
             }
-        } else {
-            format!("  [{}] {}", idx + 1, task.title)
+            Some(DueHint::Soon(when)) => {
+                format!("{} [{}] {} ({})", crate::ui::glyph("⏰"), idx + 1, task.title, when)
+            }
+            Some(DueHint::Later(when)) => format!("  [{}] {} ({})", idx + 1, task.title, when),
+            None => format!("  [{}] {}", idx + 1, task.title),
         };
         message_parts.push(task_info);
     }
 
-    let message = message_parts.join("\n");
-    send_reminder(&message, config)?;
+    Some(message_parts.join("\n"))
+}
 
-    Ok(())
+/// How a reminder describes a task's due date
+#[derive(Debug, PartialEq)]
+pub enum DueHint {
+    Overdue,
+    /// Within the due-soon window, e.g. "due in 3h" or "due today"
+    Soon(String),
+    /// Further out, e.g. "due in 4 days"
+    Later(String),
+}
+
+/// Describe when `task` is due relative to `now`. All-day tasks are counted
+/// in calendar days, timed tasks by the remaining duration; a task inside the
+/// overdue grace period is "due now".
+pub fn due_hint(task: &Task, now: DateTime<Utc>, config: &ReminderConfig) -> Option<DueHint> {
+    let due = task.due_at?;
+    if task.is_overdue_at(now, config.overdue_grace_minutes) {
+        return Some(DueHint::Overdue);
+    }
+    let remaining = due.signed_duration_since(now);
+    if remaining <= Duration::zero() {
+        return Some(DueHint::Soon("due now".to_string()));
+    }
+
+    let when = if task.all_day {
+        match (due.date_naive() - now.date_naive()).num_days() {
+            0 => "due today".to_string(),
+            1 => "due tomorrow".to_string(),
+            days => format!("due in {} days", days),
+        }
+    } else if remaining < Duration::days(2) {
+        format!("due in {}", crate::ui::format_duration(remaining))
+    } else {
+        format!("due in {} days", remaining.num_days())
+    };
+
+    if remaining <= Duration::hours(config.reminder_due_soon_hours as i64) {
+        Some(DueHint::Soon(when))
+    } else {
+        Some(DueHint::Later(when))
+    }
+}
+
+/// Take the advisory reminder lock, or `None` if another process holds it.
+/// The lock is released when the returned file is dropped.
+fn try_lock_reminders(path: &Path) -> Result<Option<File>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A task due on the given day, stored the way date-only input is
+    fn all_day_task(year: i32, month: u32, day: u32) -> Task {
+        let mut task = Task::sample("All day");
+        task.due_at = Some(Utc.with_ymd_and_hms(year, month, day, 23, 59, 59).unwrap());
+        task.all_day = true;
+        task
+    }
+
+    #[test]
+    fn logind_not_idle() {
+        let now = Utc::now();
+        assert_eq!(parse_logind_idle("IdleHint=no\nIdleSinceHint=0\n", now), Some(0));
+        assert_eq!(parse_logind_idle("IdleHint=no\n", now), Some(0));
+    }
+
+    #[test]
+    fn logind_idle_since_hint_in_micros() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+        let since = Utc.with_ymd_and_hms(2026, 10, 15, 11, 15, 30).unwrap();
+        let output = format!("IdleHint=yes\nIdleSinceHint={}\n", since.timestamp_micros());
+        assert_eq!(parse_logind_idle(&output, now), Some(44));
+        // A hint slightly in the future (clock skew) counts as just idle
+        let output = format!("IdleHint=yes\nIdleSinceHint={}\n", (now + Duration::minutes(1)).timestamp_micros());
+        assert_eq!(parse_logind_idle(&output, now), Some(0));
+    }
+
+    #[test]
+    fn logind_idle_without_usable_since_hint() {
+        let now = Utc::now();
+        assert_eq!(parse_logind_idle("IdleHint=yes\n", now), None);
+        assert_eq!(parse_logind_idle("IdleHint=yes\nIdleSinceHint=0\n", now), None);
+        assert_eq!(parse_logind_idle("IdleHint=yes\nIdleSinceHint=soon\n", now), None);
+    }
+
+    #[test]
+    fn logind_garbage_output() {
+        let now = Utc::now();
+        assert_eq!(parse_logind_idle("", now), None);
+        assert_eq!(parse_logind_idle("Failed to get user: No such user\n", now), None);
+        assert_eq!(parse_logind_idle("IdleHintish=yes\n", now), None);
+    }
+
+    #[test]
+    fn no_message_once_every_task_is_completed() {
+        let db = crate::database::Database::in_memory().unwrap();
+        let config = ReminderConfig::default();
+        let ids: Vec<i64> = ["a", "b"].iter().map(|t| db.add_task(&Task::sample(t)).unwrap()).collect();
+        assert!(build_reminder_message(&db.list_tasks(false).unwrap(), &config).is_some());
+
+        db.complete_tasks(&ids, None).unwrap();
+        assert_eq!(build_reminder_message(&db.list_tasks(false).unwrap(), &config), None);
+        assert_eq!(build_reminder_message(&db.list_tasks(true).unwrap(), &config), None);
+    }
+
+    #[test]
+    fn reminders_skip_non_actionable_snoozed_and_no_remind_tasks() {
+        let mut completed = Task::sample("completed");
+        completed.completed_at = Some(Utc::now());
+        let mut blocked = Task::sample("blocked");
+        blocked.blocked = true;
+        let mut snoozed = Task::sample("snoozed");
+        snoozed.snoozed_until = Some(Utc::now() + Duration::hours(1));
+        let mut quiet = Task::sample("no-remind");
+        quiet.reminders_enabled = false;
+        let config = ReminderConfig::default();
+
+        let tasks = [completed, blocked, snoozed, quiet];
+        assert_eq!(build_reminder_message(&tasks, &config), None);
+
+        let mut tasks = tasks.to_vec();
+        tasks.push(Task::sample("open"));
+        let message = build_reminder_message(&tasks, &config).unwrap();
+        assert!(message.contains("open"));
+        for skipped in ["completed", "blocked", "snoozed", "no-remind"] {
+            assert!(!message.contains(skipped), "{} should not be in {:?}", skipped, message);
+        }
+    }
+
+    #[test]
+    fn date_only_task_due_today_is_due_soon() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let hint = due_hint(&all_day_task(2024, 3, 10), now, &ReminderConfig::default());
+        assert_eq!(hint, Some(DueHint::Soon("due today".to_string())));
+    }
+
+    #[test]
+    fn date_only_task_days_out_counts_calendar_days() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 23, 0, 0).unwrap();
+        let hint = due_hint(&all_day_task(2024, 3, 14), now, &ReminderConfig::default());
+        assert_eq!(hint, Some(DueHint::Later("due in 4 days".to_string())));
+    }
+
+    #[test]
+    fn timed_task_days_out_counts_whole_days() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let mut task = Task::sample("Timed");
+        task.due_at = Some(now + Duration::days(3) + Duration::hours(5));
+        let hint = due_hint(&task, now, &ReminderConfig::default());
+        assert_eq!(hint, Some(DueHint::Later("due in 3 days".to_string())));
+    }
 }
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use chrono::Local;
+use colored::*;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::database::Database;
+use crate::ui::{self, glyph};
+
+/// Redraw a read-only dashboard every `interval` until `q`, Esc or Ctrl-C.
+/// The terminal is restored even if a refresh fails or panics.
+pub fn run(db_path: &Path, interval: Duration) -> Result<()> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+
+    dashboard_loop(&mut stdout, db_path, interval)
+}
+
+/// Leaves the alternate screen and raw mode when dropped, including on an
+/// early `?` return or a panic
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn dashboard_loop(stdout: &mut io::Stdout, db_path: &Path, interval: Duration) -> Result<()> {
+    loop {
+        let lines = snapshot(db_path)?;
+        queue!(stdout, Clear(ClearType::All))?;
+        for (row, line) in lines.iter().enumerate() {
+            queue!(stdout, MoveTo(0, row as u16), Print(line))?;
+        }
+        stdout.flush()?;
+
+        if wait_for_quit(interval)? {
+            return Ok(());
+        }
+    }
+}
+
+/// Render the dashboard lines from a fresh connection, so changes made by
+/// other `todo` commands show up on the next refresh
+fn snapshot(db_path: &Path) -> Result<Vec<String>> {
+    let db = Database::new(db_path.to_path_buf())?;
+    let config = db.get_config()?;
+    let tasks = db.list_tasks(true)?;
+
+    let pending = tasks.iter().filter(|t| !t.is_completed()).count();
+    let overdue = tasks.iter().filter(|t| t.is_overdue(&config)).count();
+    let done_today = ui::count_completed_on(&tasks, Local::now().date_naive(), &Local);
+
+    let mut lines = vec![
+        format!("{} todo top", glyph("📊")).bold().to_string(),
+        format!("Updated {} {} press q to quit", Local::now().format("%H:%M:%S"), glyph("·"))
+            .dimmed()
+            .to_string(),
+        String::new(),
+        format!("{} Pending: {}", "•".dimmed(), pending.to_string().yellow()),
+        format!("{} Overdue: {}", "•".dimmed(), overdue.to_string().red().bold()),
+    ];
+    match config.daily_goal {
+        Some(goal) => lines.push(format!("{} Daily goal: {}", "•".dimmed(), ui::goal_progress(done_today, goal))),
+        None => lines.push(format!("{} Completed today: {}", "•".dimmed(), done_today.to_string().green())),
+    }
+
+    lines.push(String::new());
+    lines.push(format!("{} Next Task", glyph("🎯")).bold().underline().to_string());
+    match db.get_next_task()? {
+        Some(task) => lines.extend(ui::format_task(&task, false, &config).lines().map(String::from)),
+        None => lines.push(format!("{} No pending tasks", glyph("✨"))),
+    }
+    Ok(lines)
+}
+
+/// Wait up to `timeout` for a quit key; other keys are ignored
+fn wait_for_quit(timeout: Duration) -> Result<bool> {
+    let deadline = Instant::now() + timeout;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if !event::poll(remaining)? {
+            break;
+        }
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press
+                && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc))
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
@@ -1,21 +1,116 @@
-use crate::models::Task;
-use chrono::Utc;
+use crate::database::QueryResult;
+use crate::models::{Priority, ReminderConfig, Task};
+use chrono::{Local, NaiveDate, TimeZone, Utc};
 use colored::*;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub fn format_task(task: &Task, show_id: bool) -> String {
+static NO_EMOJI: AtomicBool = AtomicBool::new(false);
+
+/// Switch `glyph` to text tokens for terminals whose fonts lack emoji
+pub fn set_no_emoji(enabled: bool) {
+    NO_EMOJI.store(enabled, Ordering::Relaxed);
+}
+
+/// All emoji output goes through here: returns `emoji` unchanged, or a short
+/// text token in its place when `--no-emoji` is set
+pub fn glyph(emoji: &'static str) -> &'static str {
+    if !NO_EMOJI.load(Ordering::Relaxed) {
+        return emoji;
+    }
+
+    match emoji {
+        "🟢" => "[low]",
+        "🟡" => "[med]",
+        "🟠" => "[high]",
+        "🔴" => "[crit]",
+        "✅" => "[ok]",
+        "🔲" => "[ ]",
+        "❌" => "[no]",
+        "⚠️" => "[!]",
+        "✨" => "*",
+        "🎉" => ":)",
+        "📋" => "[list]",
+        "📝" => "[task]",
+        "📊" => "[stats]",
+        "🔧" => "[config]",
+        "🧪" => "[test]",
+        "🎯" => "[next]",
+        "🎲" => "[pick]",
+        "⏩" => "[defer]",
+        "💤" => "[snoozed]",
+        "🔕" => "[no-remind]",
+        "⏸️" => "[blocked]",
+        "▶️" => "[unblocked]",
+        "🔔" => "[remind]",
+        "🔇" => "[quiet]",
+        "📢" => "[remind]",
+        "🔀" => "[swap]",
+        "➕" => "[+]",
+        "➖" => "[-]",
+        "🗑️" => "[deleted]",
+        "🧹" => "[clear]",
+        "⏰" => "[due]",
+        "📅" => "[due]",
+        "⏱️" => "[est]",
+        "☑️" => "[steps]",
+        "📁" => "[project]",
+        "📎" => "[attached]",
+        "🔮" => "[forecast]",
+        "·" => "-",
+        _ => emoji,
+    }
+}
+
+/// "✅ Yes" / "❌ No" for boolean settings
+pub fn yes_no(value: bool) -> String {
+    if value {
+        format!("{} Yes", glyph("✅"))
+    } else {
+        format!("{} No", glyph("❌"))
+    }
+}
+
+/// Output width in columns: the terminal size when stdout is a TTY,
+/// otherwise `COLUMNS` if set. `None` (piped output without `COLUMNS`) means
+/// there is no width to fit, so nothing should be truncated.
+pub fn terminal_width() -> Option<usize> {
+    if std::io::stdout().is_terminal() {
+        if let Ok((cols, _)) = crossterm::terminal::size() {
+            return Some(cols as usize);
+        }
+    }
+    parse_columns(std::env::var("COLUMNS").ok().as_deref())
+}
+
+fn parse_columns(value: Option<&str>) -> Option<usize> {
+    value?.trim().parse().ok().filter(|&cols| cols > 0)
+}
+
+/// A horizontal rule of `ch`, at most `max` wide and shrunk to fit the output
+pub fn rule(ch: &str, max: usize) -> String {
+    ch.repeat(terminal_width().map_or(max, |width| max.min(width)))
+}
+
+pub fn format_task(task: &Task, show_id: bool, config: &ReminderConfig) -> String {
     let id_str = if show_id {
         format!("[{}] ", task.id)
     } else {
         String::new()
     };
 
-    let priority_icon = task.priority.as_str();
+    format_task_lines(task, &id_str, config)
+}
+
+/// Render a task as multi-line text, with `prefix` before the title line
+fn format_task_lines(task: &Task, prefix: &str, config: &ReminderConfig) -> String {
+    let priority_icon = glyph(task.priority.as_str());
     
     // Add completion badge
     let status_badge = if task.is_completed() {
-        "✅".green().to_string()
+        glyph("✅").green().to_string()
     } else {
-        "🔲".dimmed().to_string()
+        glyph("🔲").dimmed().to_string()
     };
     
     let title = if task.is_completed() {
@@ -24,21 +119,34 @@ pub fn format_task(task: &Task, show_id: bool) -> String {
         task.title.bold()
     };
 
-    let mut parts = vec![format!("{}{}{} {}", id_str, status_badge, priority_icon, title)];
+    let label = task
+        .label
+        .as_ref()
+        .map(|l| format!("{} ", l))
+        .unwrap_or_default();
+
+    let mut title_line = format!("{}{}{} {}{}", prefix, status_badge, priority_icon, label, title);
+    if task.blocked {
+        title_line.push_str(&format!(" {}", format!("{} blocked", glyph("⏸️")).yellow()));
+    }
+    if !task.reminders_enabled {
+        title_line.push_str(&format!(" {}", glyph("🔕").dimmed()));
+    }
+    let mut parts = vec![title_line];
 
     if let Some(ref desc) = task.description {
         parts.push(format!("   {}", desc.dimmed()));
     }
 
     if let Some(ref project) = task.project {
-        parts.push(format!("   📁 {}", project.cyan()));
+        parts.push(format!("   {} {}", glyph("📁"), project.cyan()));
     }
 
     if !task.tags.is_empty() {
         let tags: Vec<String> = task
             .tags
             .iter()
-            .map(|t| format!("#{}", t.green()))
+            .map(|t| format!("{}{}", config.tag_prefix, t.green()))
             .collect();
         parts.push(format!("   {}", tags.join(" ")));
     }
@@ -46,29 +154,156 @@ pub fn format_task(task: &Task, show_id: bool) -> String {
     if let Some(due) = task.due_at {
         let now = Utc::now();
         let duration = due.signed_duration_since(now);
-        let due_str = if duration.num_hours() < 0 {
-            format!("   ⚠️ Overdue by {}", format_duration(duration.abs()))
+        let due_str = if task.is_overdue(config) {
+            format!("   {} Overdue by {}", glyph("⚠️"), format_duration(duration.abs()))
+        } else if duration < chrono::Duration::zero() {
+            format!("   {} Due now", glyph("⏰"))
+        } else if duration.num_hours() < 24 && task.all_day {
+            format!("   {} Due today", glyph("⏰"))
         } else if duration.num_hours() < 24 {
-            format!("   ⏰ Due in {}", format_duration(duration))
+            format!("   {} Due in {}", glyph("⏰"), format_duration(duration))
         } else {
-            format!("   📅 {}", due.format("%Y-%m-%d %H:%M"))
+            format!("   {} {}", glyph("📅"), format_due(task))
         };
         parts.push(due_str.yellow().to_string());
     }
 
-    if let Some(mins) = task.estimated_minutes {
-        parts.push(format!("   ⏱️  Est. {} min", mins));
+    match task.estimated_minutes {
+        Some(mins) => parts.push(format!("   {}  Est. {} min", glyph("⏱️"), mins)),
+        None if config.estimate_hint && !task.is_completed() => {
+            parts.push(format!("   {}  no estimate", glyph("⏱️")).dimmed().to_string());
+        }
+        None => {}
+    }
+
+    if !task.checklist.is_empty() {
+        let done = task.checklist.iter().filter(|item| item.done).count();
+        parts.push(format!("   {}  {}/{} steps", glyph("☑️"), done, task.checklist.len()));
+    }
+
+    if !task.attachments.is_empty() {
+        let count = task.attachments.len();
+        parts.push(format!("   {} {} attachment{}", glyph("📎"), count, if count == 1 { "" } else { "s" }));
+    }
+
+    if let Some(until) = task.snoozed_until.filter(|_| task.is_snoozed()) {
+        parts.push(format!("   {} Snoozed until {}", glyph("💤"), until.format("%Y-%m-%d %H:%M")).dimmed().to_string());
     }
 
     // Add completion timestamp if task is done
     if let Some(completed) = task.completed_at {
-        parts.push(format!("   ✨ Completed: {}", completed.format("%Y-%m-%d %H:%M").to_string().green()));
+        parts.push(format!("   {} Completed: {}", glyph("✨"), completed.format("%Y-%m-%d %H:%M").to_string().green()));
     }
 
     parts.join("\n")
 }
 
-fn format_duration(duration: chrono::Duration) -> String {
+/// The due date alone for all-day tasks, otherwise date and time
+pub fn format_due(task: &Task) -> String {
+    match task.due_at {
+        Some(due) if task.all_day => due.format("%Y-%m-%d").to_string(),
+        Some(due) => due.format("%Y-%m-%d %H:%M").to_string(),
+        None => String::new(),
+    }
+}
+
+/// Render a task as a single line: status, priority, title and completion date
+pub fn format_task_compact(task: &Task) -> String {
+    let status = if task.is_completed() { glyph("✅") } else { glyph("🔲") };
+    let label = task.label.as_ref().map(|l| format!("{} ", l)).unwrap_or_default();
+    let mut line = format!("  {} {} {}{}", status, glyph(task.priority.as_str()), label, task.title);
+    if task.blocked {
+        line.push_str(&format!(" {} blocked", glyph("⏸️")));
+    }
+    if !task.reminders_enabled {
+        line.push_str(&format!(" {}", glyph("🔕")));
+    }
+    if let Some(completed) = task.completed_at {
+        let date = format!("(completed {})", completed.format("%Y-%m-%d"));
+        line.push_str(&format!(" {}", date.dimmed()));
+    }
+    line
+}
+
+/// Render a task's attachments as numbered lines, linked where the
+/// terminal can follow them
+pub fn format_attachments(task: &Task) -> String {
+    let mut lines = vec![format!("{} ({})", "Attachments".bold(), task.attachments.len())];
+    for (idx, reference) in task.attachments.iter().enumerate() {
+        let target = if reference.contains("://") {
+            reference.clone()
+        } else {
+            format!("file://{}", reference)
+        };
+        lines.push(format!("  {}. {}", idx + 1, hyperlink(&target, reference)));
+    }
+    lines.join("\n")
+}
+
+/// Wrap `text` in an OSC 8 hyperlink to `target` when writing to a terminal
+fn hyperlink(target: &str, text: &str) -> String {
+    let supported = std::io::stdout().is_terminal()
+        && std::env::var("TERM").map_or(true, |term| term != "dumb");
+    if supported {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", target, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Render a task's checklist as numbered `[x]`/`[ ]` lines under a progress header
+pub fn format_checklist(task: &Task) -> String {
+    let done = task.checklist.iter().filter(|item| item.done).count();
+    let mut lines = vec![format!(
+        "{} ({}/{})",
+        "Checklist".bold(),
+        done,
+        task.checklist.len()
+    )];
+
+    for (idx, item) in task.checklist.iter().enumerate() {
+        let line = if item.done {
+            format!("  [x] {}. {}", idx + 1, item.text.dimmed())
+        } else {
+            format!("  [ ] {}. {}", idx + 1, item.text)
+        };
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+/// "3h 5m ago" for a past time. A time in the future (a task created while
+/// the clock was wrong) or under a minute ago reads "just now".
+pub fn format_age(at: chrono::DateTime<Utc>, now: chrono::DateTime<Utc>) -> String {
+    let age = now.signed_duration_since(at);
+    if age < chrono::Duration::minutes(1) {
+        "just now".to_string()
+    } else {
+        format!("{} ago", format_duration(age))
+    }
+}
+
+/// How much longer than its estimate a task may have been open and still be
+/// compared with it; past this, the open time says little about the effort
+const ESTIMATE_COMPARE_FACTOR: i64 = 4;
+
+/// For a completed task with an estimate: "Estimated 60m, took 85m (+42%)",
+/// timing from creation to completion. When the task stayed open far longer
+/// than estimated, only the estimate and the open time are shown.
+pub fn estimate_summary(task: &Task) -> Option<String> {
+    let estimate = i64::from(task.estimated_minutes.filter(|&m| m > 0)?);
+    let elapsed = task.completed_at?.signed_duration_since(task.created_at);
+    let took = elapsed.num_minutes().max(0);
+
+    if took > estimate * ESTIMATE_COMPARE_FACTOR {
+        return Some(format!("Estimated {}m (open for {})", estimate, format_duration(elapsed)));
+    }
+    let change = ((took - estimate) as f64 * 100.0 / estimate as f64).round() as i64;
+    Some(format!("Estimated {}m, took {}m ({:+}%)", estimate, took, change))
+}
+
+pub fn format_duration(duration: chrono::Duration) -> String {
     let days = duration.num_days();
     let hours = duration.num_hours() % 24;
     let minutes = duration.num_minutes() % 60;
@@ -86,90 +321,309 @@ fn format_duration(duration: chrono::Duration) -> String {
     }
 }
 
-pub fn print_task_list(tasks: &[Task], title: &str) {
+/// Empty-state for a filtered list: name the filters that matched nothing and
+/// how many pending tasks exist overall, so a typo is easy to spot
+pub fn print_no_matches(title: &str, filters: &[String], pending: u64) {
+    println!("\n{}", title.bold().underline());
+    println!("{}", rule("═", 60));
+    println!(
+        "\n  {} No tasks found matching {} ({} pending task{} total)\n",
+        glyph("✨").dimmed(),
+        filters.join(", "),
+        pending,
+        if pending == 1 { "" } else { "s" }
+    );
+}
+
+/// Print numbered tasks; `show_id` adds each task's stable database id
+pub fn print_task_list(tasks: &[Task], title: &str, show_id: bool, config: &ReminderConfig) {
     println!("\n{}", title.bold().underline());
-    println!("{}", "═".repeat(60));
+    println!("{}", rule("═", 60));
 
     if tasks.is_empty() {
-        println!("\n  {} No tasks found\n", "✨".dimmed());
+        println!("\n  {} No tasks found\n", glyph("✨").dimmed());
     } else {
         for (index, task) in tasks.iter().enumerate() {
             // Use sequential index instead of database ID
-            let display_task = format_task_with_index(task, index + 1);
+            let display_task = format_task_with_index(task, index + 1, show_id, config);
             println!("\n{}", display_task);
             if index < tasks.len() - 1 {
-                println!("{}", "─".repeat(60).dimmed());
+                println!("{}", rule("─", 60).dimmed());
             }
         }
-        println!("\n{}", "═".repeat(60));
-        println!("  Total: {} task{}\n", tasks.len(), if tasks.len() != 1 { "s" } else { "" });
+        println!("\n{}", rule("═", 60));
+        println!("  {}\n", list_footer(tasks));
     }
 }
 
-fn format_task_with_index(task: &Task, index: usize) -> String {
-    let index_str = format!("[{}] ", index);
-    let priority_icon = task.priority.as_str();
-    
-    // Add completion badge
-    let status_badge = if task.is_completed() {
-        "✅".green().to_string()
+/// "Total: 3 tasks", or "42 total · 30 completed (71%)" once any are done
+fn list_footer(tasks: &[Task]) -> String {
+    let total = tasks.len();
+    let completed = tasks.iter().filter(|t| t.is_completed()).count();
+    if completed == 0 {
+        return format!("Total: {} task{}", total, if total != 1 { "s" } else { "" });
+    }
+    format!("{} total {} {} completed ({}%)", total, glyph("·"), completed, completed * 100 / total)
+}
+
+fn format_task_with_index(task: &Task, index: usize, show_id: bool, config: &ReminderConfig) -> String {
+    let prefix = if show_id {
+        format!("[{}] {} ", index, format!("(id {})", task.id).dimmed())
     } else {
-        "🔲".dimmed().to_string()
+        format!("[{}] ", index)
     };
-    
-    let title = if task.is_completed() {
-        task.title.strikethrough().dimmed()
+    format_task_lines(task, &prefix, config)
+}
+
+/// Print query results as an aligned table, one row per line
+pub fn print_query_result(result: &QueryResult) {
+    let cells: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value {
+                    serde_json::Value::Null => "NULL".to_string(),
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    print_table(&result.columns, &cells, None);
+    println!("{}", format!("({} row{})", cells.len(), if cells.len() == 1 { "" } else { "s" }).dimmed());
+}
+
+/// One task for `list --porcelain`: id, status (pending, blocked or done),
+/// priority weight (1-4), due time as Unix seconds and title, separated by
+/// tabs. Missing values are "-". This format is a stable interface; new
+/// fields may only ever be appended.
+pub fn porcelain_line(task: &Task) -> String {
+    let status = if task.is_completed() {
+        "done"
+    } else if task.blocked {
+        "blocked"
     } else {
-        task.title.bold()
+        "pending"
     };
+    let due = task.due_at.map(|d| d.timestamp().to_string()).unwrap_or_else(|| "-".to_string());
+    // Keep each task on one line with exactly five fields
+    let title: String = task.title.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    format!("{}\t{}\t{}\t{}\t{}", task.id, status, task.priority.weight(), due, title)
+}
 
-    let mut parts = vec![format!("{}{}{} {}", index_str, status_badge, priority_icon, title)];
+/// Columns available to `print_task_table`
+#[derive(Debug, Clone, Copy)]
+pub enum TaskColumn {
+    Id,
+    Index,
+    Priority,
+    Title,
+    Project,
+    Tags,
+    Due,
+    Estimate,
+    Created,
+}
 
-    if let Some(ref desc) = task.description {
-        parts.push(format!("   {}", desc.dimmed()));
-    }
+/// Every column, for `list --format wide`
+pub const WIDE_COLUMNS: [TaskColumn; 9] = [
+    TaskColumn::Index,
+    TaskColumn::Id,
+    TaskColumn::Priority,
+    TaskColumn::Title,
+    TaskColumn::Project,
+    TaskColumn::Tags,
+    TaskColumn::Due,
+    TaskColumn::Estimate,
+    TaskColumn::Created,
+];
 
-    if let Some(ref project) = task.project {
-        parts.push(format!("   📁 {}", project.cyan()));
+impl TaskColumn {
+    fn header(self) -> &'static str {
+        match self {
+            TaskColumn::Id => "ID",
+            TaskColumn::Index => "#",
+            TaskColumn::Priority => "Priority",
+            TaskColumn::Title => "Title",
+            TaskColumn::Project => "Project",
+            TaskColumn::Tags => "Tags",
+            TaskColumn::Due => "Due",
+            TaskColumn::Estimate => "Est",
+            TaskColumn::Created => "Created",
+        }
     }
 
-    if !task.tags.is_empty() {
-        let tags: Vec<String> = task.tags.iter().map(|t| format!("#{}", t.green())).collect();
-        parts.push(format!("   {}", tags.join(" ")));
+    fn cell(self, task: &Task, index: usize, config: &ReminderConfig) -> String {
+        match self {
+            TaskColumn::Id => task.id.to_string(),
+            TaskColumn::Index => index.to_string(),
+            TaskColumn::Priority => task.priority.label(config),
+            // A single-width mark, so the column stays aligned
+            TaskColumn::Title if task.is_completed() => format!("✓ {}", task.title),
+            TaskColumn::Title => task.title.clone(),
+            TaskColumn::Project => task.project.clone().unwrap_or_default(),
+            TaskColumn::Tags => task
+                .tags
+                .iter()
+                .map(|t| format!("{}{}", config.tag_prefix, t))
+                .collect::<Vec<_>>()
+                .join(" "),
+            TaskColumn::Due => format_due(task),
+            TaskColumn::Estimate => task.estimated_minutes.map(|m| format!("{}m", m)).unwrap_or_default(),
+            TaskColumn::Created => task.created_at.format("%Y-%m-%d").to_string(),
+        }
     }
+}
 
-    if let Some(due) = task.due_at {
-        let now = Utc::now();
-        let duration = due.signed_duration_since(now);
-        let due_str = if duration.num_hours() < 0 {
-            format!("   ⚠️ Overdue by {}", format_duration(duration.abs()))
-        } else if duration.num_hours() < 24 {
-            format!("   ⏰ Due in {}", format_duration(duration))
-        } else {
-            format!("   📅 {}", due.format("%Y-%m-%d %H:%M"))
-        };
-        parts.push(due_str.yellow().to_string());
+/// Print tasks as a table of the given columns, numbered like `print_task_list`
+pub fn print_task_table(tasks: &[Task], title: &str, columns: &[TaskColumn], config: &ReminderConfig) {
+    println!("\n{}", title.bold().underline());
+    if tasks.is_empty() {
+        println!("\n  {} No tasks found\n", glyph("✨").dimmed());
+        return;
     }
 
-    if let Some(mins) = task.estimated_minutes {
-        parts.push(format!("   ⏱️  Est. {} min", mins));
+    let headers: Vec<String> = columns.iter().map(|c| c.header().to_string()).collect();
+    let rows: Vec<Vec<String>> = tasks
+        .iter()
+        .enumerate()
+        .map(|(idx, task)| columns.iter().map(|c| c.cell(task, idx + 1, config)).collect())
+        .collect();
+    let title_column = columns.iter().position(|c| matches!(c, TaskColumn::Title));
+    println!();
+    print_table(&headers, &rows, title_column);
+    println!("\n  {}\n", list_footer(tasks));
+}
+
+/// One task in `todo forecast`
+pub struct ForecastRow<'a> {
+    /// Position in `todo list`, for addressing the task
+    pub index: usize,
+    pub task: &'a Task,
+    /// Minutes assumed for the task
+    pub estimate: u32,
+    /// Whether `estimate` is the configured default rather than the task's own
+    pub defaulted: bool,
+    pub finish: chrono::NaiveDate,
+}
+
+/// Print `todo forecast`: each task with its estimate (`~` marks the default
+/// estimate) and projected finish date, then when the whole queue is cleared
+pub fn print_forecast(rows: &[ForecastRow], config: &ReminderConfig) {
+    println!("\n{}", format!("{} Forecast", glyph("🔮")).bold().underline());
+    let Some(cleared_by) = rows.last().map(|row| row.finish) else {
+        println!("\n  {} No actionable tasks to forecast\n", glyph("✨").dimmed());
+        return;
+    };
+
+    let headers: Vec<String> = ["#", "Priority", "Title", "Est", "Finish"].iter().map(|h| h.to_string()).collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.index.to_string(),
+                row.task.priority.label(config),
+                row.task.title.clone(),
+                format!("{}{}m", if row.defaulted { "~" } else { "" }, row.estimate),
+                row.finish.format("%a %Y-%m-%d").to_string(),
+            ]
+        })
+        .collect();
+    println!();
+    print_table(&headers, &cells, Some(2));
+
+    let total: u32 = rows.iter().map(|row| row.estimate).sum();
+    println!(
+        "\n  Queue cleared by {} {} {} of work at {} a day\n",
+        cleared_by.format("%a %Y-%m-%d").to_string().bold(),
+        glyph("·"),
+        format_duration(chrono::Duration::minutes(total.into())),
+        format_duration(chrono::Duration::minutes(config.daily_capacity_minutes.into()))
+    );
+}
+
+/// Narrowest a column is squeezed to when the table is wider than the terminal
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// Narrowest the `flexible` column of `print_table` gets before others shrink
+const MIN_FLEXIBLE_WIDTH: usize = 16;
+
+/// Print an aligned table that fits the terminal: the `flexible` column (such
+/// as a title) is shortened with "…" first, then the widest columns
+fn print_table(headers: &[String], rows: &[Vec<String>], flexible: Option<usize>) {
+    const SEPARATOR: &str = " | ";
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            rows.iter()
+                .map(|row| row[idx].chars().count())
+                .chain(std::iter::once(name.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // Without a known width (e.g. `| less -S`), print every cell in full
+    if let Some(available) = terminal_width() {
+        let separators = SEPARATOR.len() * widths.len().saturating_sub(1);
+        let overflow = (widths.iter().sum::<usize>() + separators).saturating_sub(available);
+        if let Some(width) = flexible.and_then(|idx| widths.get_mut(idx)) {
+            *width = width.saturating_sub(overflow).max(MIN_FLEXIBLE_WIDTH.min(*width));
+        }
+        while widths.iter().sum::<usize>() + separators > available {
+            let Some(widest) = widths.iter_mut().filter(|w| **w > MIN_COLUMN_WIDTH).max() else {
+                break;
+            };
+            *widest -= 1;
+        }
     }
 
-    // Add completion timestamp if task is done
-    if let Some(completed) = task.completed_at {
-        parts.push(format!("   ✨ Completed: {}", completed.format("%Y-%m-%d %H:%M").to_string().green()));
+    let format_row = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, &width)| format!("{:<width$}", truncate(value, width), width = width))
+            .collect::<Vec<_>>()
+            .join(SEPARATOR)
+    };
+
+    println!("{}", format_row(headers).bold());
+    println!("{}", widths.iter().map(|w| "─".repeat(*w)).collect::<Vec<_>>().join("─┼─").dimmed());
+    for row in rows {
+        println!("{}", format_row(row));
     }
+}
 
-    parts.join("\n")
+/// Cut `text` to `width` characters, ending in "…" when shortened
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
 }
 
-pub fn print_stats(tasks: &[Task]) {
+/// Print task statistics; `scope` describes any filters already applied to `tasks`
+pub fn print_stats(tasks: &[Task], config: &ReminderConfig, scope: Option<&str>) {
     let total = tasks.len();
     let completed = tasks.iter().filter(|t| t.is_completed()).count();
+    let week_begin = config.week_start.week_start_date(Local::now().date_naive());
+    let completed_this_week = tasks
+        .iter()
+        .filter_map(|t| t.completed_at)
+        .filter(|at| at.with_timezone(&Local).date_naive() >= week_begin)
+        .count();
     let pending = total - completed;
-    let overdue = tasks.iter().filter(|t| t.is_overdue()).count();
+    let overdue = tasks.iter().filter(|t| t.is_overdue(config)).count();
 
-    println!("\n{}", "📊 Statistics".bold());
+    println!("\n{}", format!("{} Statistics", glyph("📊")).bold());
+    if let Some(scope) = scope {
+        println!("{}", format!("  Scope: {}", scope).dimmed());
+    }
     println!("{} Total: {}", "•".dimmed(), total);
     println!("{} Pending: {}", "•".dimmed(), pending.to_string().yellow());
     println!(
@@ -177,6 +631,15 @@ pub fn print_stats(tasks: &[Task]) {
         "•".dimmed(),
         completed.to_string().green()
     );
+    println!(
+        "{} Completed this week: {}",
+        "•".dimmed(),
+        completed_this_week.to_string().green()
+    );
+    if let Some(goal) = config.daily_goal {
+        let done_today = count_completed_on(tasks, Local::now().date_naive(), &Local);
+        println!("{} Daily goal: {}", "•".dimmed(), goal_progress(done_today, goal));
+    }
     if overdue > 0 {
         println!(
             "{} Overdue: {}",
@@ -184,5 +647,207 @@ pub fn print_stats(tasks: &[Task]) {
             overdue.to_string().red().bold()
         );
     }
+    if pending > 0 {
+        println!("{} Load: {}", "•".dimmed(), priority_sparkline(tasks));
+    }
     println!();
 }
+
+/// Count tasks completed on `day`, where day boundaries are midnight in `tz`
+pub fn count_completed_on<Tz: TimeZone>(tasks: &[Task], day: NaiveDate, tz: &Tz) -> usize {
+    tasks
+        .iter()
+        .filter_map(|t| t.completed_at)
+        .filter(|at| at.with_timezone(tz).date_naive() == day)
+        .count()
+}
+
+/// Width in cells of the daily goal progress bar
+const GOAL_BAR_WIDTH: usize = 10;
+
+/// Render "3/5 completed today (60%)" followed by a bar capped at the goal
+pub fn goal_progress(done: usize, goal: u32) -> String {
+    let goal = goal as usize;
+    let percent = (done * 100).checked_div(goal).unwrap_or(100);
+    let filled = block_bar(done.min(goal), goal, GOAL_BAR_WIDTH);
+    let bar = format!("{:<width$}", filled, width = GOAL_BAR_WIDTH);
+    let bar = if done >= goal { bar.green() } else { bar.yellow() };
+    format!("{}/{} completed today ({}%) [{}]", done, goal, percent, bar)
+}
+
+/// Width in cells of the longest bar in the priority sparkline
+const SPARKLINE_WIDTH: usize = 8;
+
+/// Render pending task counts per priority as proportional block bars
+fn priority_sparkline(tasks: &[Task]) -> String {
+    let priorities = [Priority::Low, Priority::Medium, Priority::High, Priority::Critical];
+    let counts: Vec<usize> = priorities
+        .iter()
+        .map(|p| {
+            tasks
+                .iter()
+                .filter(|t| !t.is_completed() && &t.priority == p)
+                .count()
+        })
+        .collect();
+    let max = counts.iter().copied().max().unwrap_or(0);
+
+    priorities
+        .iter()
+        .zip(counts)
+        .map(|(priority, count)| {
+            let bar = block_bar(count, max, SPARKLINE_WIDTH);
+            let bar = match priority {
+                Priority::Low => bar.green(),
+                Priority::Medium => bar.yellow(),
+                Priority::High => bar.bright_red(),
+                Priority::Critical => bar.red(),
+            };
+            format!("{}{}", glyph(priority.as_str()), bar)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Build a bar of `width` cells scaled to `value / max`, using eighth blocks
+fn block_bar(value: usize, max: usize, width: usize) -> String {
+    const PARTIALS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+    if max == 0 || value == 0 {
+        return String::new();
+    }
+
+    // Never round a non-zero count down to an invisible bar
+    let eighths = (value * width * 8 / max).max(1);
+    let mut bar = "█".repeat(eighths / 8);
+    let remainder = eighths % 8;
+    if remainder > 0 {
+        bar.push(PARTIALS[remainder]);
+    }
+    bar
+}
+
+pub fn print_config(config: &ReminderConfig) {
+    println!("\n{}", format!("{} Current Reminder Configuration", glyph("🔧")).bold().underline());
+    println!("{}", rule("═", 50));
+    println!("  Enabled: {}", yes_no(config.enabled));
+
+    // Format interval
+    let hours = config.interval_minutes / 60;
+    let mins = config.interval_minutes % 60;
+    if hours > 0 && mins > 0 {
+        println!("  Interval: {}h {}m", hours, mins);
+    } else if hours > 0 {
+        println!("  Interval: {}h", hours);
+    } else {
+        println!("  Interval: {}m", mins);
+    }
+
+    println!("  Desktop Notifications: {}", yes_no(config.use_notify_send));
+    println!("  Terminal Wall Messages: {}", yes_no(config.use_wall));
+    println!("  File Sink: {}", yes_no(config.use_file_sink));
+    if config.use_file_sink {
+        let mode = if config.file_sink_append { "append" } else { "overwrite" };
+        println!("  File Sink Path: {} ({})", crate::reminders::file_sink_path(config).display(), mode);
+    }
+    if config.use_wall {
+        println!("  Wall Quiet Hours: {}:00 - {}:00 (no wall messages)",
+                 config.wall_quiet_start_hour, config.wall_quiet_end_hour);
+        if config.start_from_quiet_end {
+            println!("  Start Time: Reminders start from quiet-end time");
+        }
+    }
+    println!("  Due Soon Window: {}h", config.reminder_due_soon_hours);
+    println!("  Overdue Grace: {}m", config.overdue_grace_minutes);
+    println!("  Remind Undated Tasks: {}", yes_no(config.remind_undated));
+    println!("  Week Starts On: {}", config.week_start.as_str());
+    println!("  Tag Prefix: {}", config.tag_prefix);
+    println!("  Lowercase Projects: {}", yes_no(config.lowercase_projects));
+    println!("  Lowercase Tags: {}", yes_no(config.lowercase_tags));
+    println!("  Strict Projects: {}", yes_no(config.strict_projects));
+    println!("  No-Estimate Hint: {}", yes_no(config.estimate_hint));
+    println!("  Completion Sound: {}", config.completion_sound.as_deref().unwrap_or("off"));
+    match config.skip_if_idle_minutes {
+        Some(minutes) => println!("  Skip If Idle: after {} minutes", minutes),
+        None => println!("  Skip If Idle: off"),
+    }
+    match config.daily_goal {
+        Some(goal) => println!("  Daily Goal: {} tasks", goal),
+        None => println!("  Daily Goal: off"),
+    }
+    println!("  Daily Capacity: {}", format_duration(chrono::Duration::minutes(config.daily_capacity_minutes.into())));
+    println!("  Default Estimate: {}m", config.default_estimate_minutes);
+    let labels: Vec<String> = [Priority::Low, Priority::Medium, Priority::High, Priority::Critical]
+        .iter()
+        .map(|p| format!("{} {}", glyph(p.as_str()), p.label(config)))
+        .collect();
+    println!("  Priority Labels: {}", labels.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn columns_from_env_value() {
+        assert_eq!(parse_columns(Some("120")), Some(120));
+        assert_eq!(parse_columns(Some(" 60 ")), Some(60));
+    }
+
+    #[test]
+    fn unusable_columns_are_ignored() {
+        assert_eq!(parse_columns(None), None);
+        assert_eq!(parse_columns(Some("")), None);
+        assert_eq!(parse_columns(Some("0")), None);
+        assert_eq!(parse_columns(Some("-5")), None);
+        assert_eq!(parse_columns(Some("wide")), None);
+    }
+
+    #[test]
+    fn future_creation_time_reads_just_now() {
+        let now = Utc::now();
+        assert_eq!(format_age(now + Duration::hours(3), now), "just now");
+        assert_eq!(format_age(now + Duration::days(400), now), "just now");
+    }
+
+    #[test]
+    fn past_creation_time_reads_as_age() {
+        let now = Utc::now();
+        assert_eq!(format_age(now - Duration::seconds(30), now), "just now");
+        assert_eq!(format_age(now - Duration::minutes(185), now), "3h 5m ago");
+        assert_eq!(format_age(now - Duration::days(2), now), "2d ago");
+    }
+
+    fn completed_at(at: chrono::DateTime<Utc>) -> Task {
+        let mut task = Task::sample("Done");
+        task.completed_at = Some(at);
+        task
+    }
+
+    #[test]
+    fn completions_count_toward_the_local_day() {
+        let utc_plus_2 = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        // 23:30 and 00:30 local on either side of midnight on March 10th
+        let tasks = [
+            completed_at(Utc.with_ymd_and_hms(2024, 3, 9, 21, 30, 0).unwrap()),
+            completed_at(Utc.with_ymd_and_hms(2024, 3, 9, 22, 30, 0).unwrap()),
+        ];
+        let march = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+
+        assert_eq!(count_completed_on(&tasks, march(9), &utc_plus_2), 1);
+        assert_eq!(count_completed_on(&tasks, march(10), &utc_plus_2), 1);
+        // Both fall on the 9th in UTC
+        assert_eq!(count_completed_on(&tasks, march(9), &Utc), 2);
+        assert_eq!(count_completed_on(&tasks, march(10), &Utc), 0);
+    }
+
+    #[test]
+    fn negative_offset_moves_early_utc_completions_back_a_day() {
+        let utc_minus_5 = chrono::FixedOffset::west_opt(5 * 3600).unwrap();
+        let tasks = [completed_at(Utc.with_ymd_and_hms(2024, 3, 10, 3, 0, 0).unwrap()), Task::sample("Open")];
+
+        assert_eq!(count_completed_on(&tasks, NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(), &utc_minus_5), 1);
+        assert_eq!(count_completed_on(&tasks, NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(), &utc_minus_5), 0);
+    }
+}